@@ -51,12 +51,41 @@ const DetailsInner = ({ hidden, setInput }: DetailsInProps) => {
             <li>
               <span class="greek">ψ</span> (psi) = j
             </li>
+            <li>
+              <span class="greek">ϝ</span> (digamma) = v
+            </li>
+            <li>
+              <span class="greek">ϙ, ϟ, ϛ, ϡ, ͱ</span>{" "}
+              (koppa, numeral koppa, stigma, sampi, heta) = #q, #k, #s, #p, #h;
+              # right after a letter is <span class="greek">ʹ</span>{" "}
+              (keraia, e.g. a# = <span class="greek">αʹ</span>), and is copied
+              as-is elsewhere
+            </li>
+            <li>
+              <span class="greek">σ, ς, ϲ</span>{" "}
+              (medial, final, lunate sigma) = s1, s2, s3, whatever follows
+            </li>
             <li>
               <span class="greek">ϊ, ϋ</span>{" "}
               (diaereses) = " (double quotation mark)
             </li>
             <li>
-              <span class="greek">᾽</span> (koronis) = ' (single quotation mark)
+              <span class="greek">᾽</span> (koronis) = ' (single quotation mark);
+              right after a letter, it is <span class="greek">’</span>{" "}
+              (elision), or the koronis of the following vowel (e.g. k'agw/ ={" "}
+              <span class="greek">κἀγώ</span>); after s, and in monotonic text,
+              it is always elision (e.g. s'agapw/ ={" "}
+              <span class="greek">σ’αγαπώ</span>)
+            </li>
+            <li>
+              <span class="greek">ᾱ, ᾰ</span>{" "}
+              (macron, breve) = _ (underscore), ^ (caret)
+            </li>
+            <li>
+              <span class="greek">·</span> (ano teleia) = : (colon)
+            </li>
+            <li>
+              <span class="greek">;</span> (question mark) = ; (semicolon)
             </li>
             <li>capital Greek = capital Latin</li>
           </ul>
@@ -64,7 +93,8 @@ const DetailsInner = ({ hidden, setInput }: DetailsInProps) => {
         <div class="mt-2">
           Any characters that are NOT converted to Greek are displayed as-is,
           and are regarded as "end-of-word" tokens; i.e., a sigma in lower case
-          is transformed to its final form.
+          is transformed to its final form. The exceptions are 1, 2 and 3 right
+          after s, which choose the form of the sigma as above.
         </div>
         <div class="mt-2">
           Click &#32;
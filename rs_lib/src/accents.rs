@@ -0,0 +1,110 @@
+use crate::syllables::{self, is, Word};
+use crate::{Accent, Letter, Quantity, WarningKind};
+
+use std::ops::Range;
+
+// The length of a syllable for accentuation: as by nature, except that a final -αι or -οι
+// counts as short.
+pub fn length(word: &Word, nucleus: &Range<usize>) -> Quantity {
+    let letters = &word.letters;
+    let (first, second) = (letters[nucleus.start], letters[nucleus.end - 1]);
+
+    let is_final = nucleus.end == letters.len() && nucleus.len() == 2;
+    if is_final
+        && (is(first, Letter::Alpha) || is(first, Letter::Omicron))
+        && is(second, Letter::Iota)
+        && second.accent() != Accent::Circumflex
+    {
+        return Quantity::Short;
+    }
+
+    syllables::length(letters, nucleus)
+}
+
+// The accent of the syllable, if any of its vowels has one.
+fn accent(word: &Word, nucleus: &Range<usize>) -> Accent {
+    word.letters[nucleus.clone()]
+        .iter()
+        .map(|&state| state.accent())
+        .find(|&accent| accent != Accent::None)
+        .unwrap_or(Accent::None)
+}
+
+// The first rule the accent of the word breaks, if any. Only the first accent counts, since a
+// second one is thrown back from an enclitic. Syllables of unknown length break no rule.
+pub fn check_word(word: &Word) -> Option<WarningKind> {
+    let nuclei = syllables::nuclei(&word.letters);
+    let (i, accent) = nuclei
+        .iter()
+        .map(|nucleus| accent(word, nucleus))
+        .enumerate()
+        .find(|&(_, accent)| accent != Accent::None)?;
+
+    let from_end = nuclei.len() - 1 - i;
+    let ultima = length(word, &nuclei[nuclei.len() - 1]);
+    let own = length(word, &nuclei[i]);
+
+    match (accent, from_end) {
+        (_, 3..) => Some(WarningKind::LawOfLimitation),
+        (Accent::Circumflex, _) if own == Quantity::Short => {
+            Some(WarningKind::CircumflexOnShortSyllable)
+        }
+        (Accent::Circumflex, 2) => Some(WarningKind::LawOfLimitation),
+        (Accent::Circumflex, 1) if ultima == Quantity::Long => Some(WarningKind::LawOfLimitation),
+        (Accent::Acute | Accent::Grave, 2) if ultima == Quantity::Long => {
+            Some(WarningKind::LawOfLimitation)
+        }
+        (Accent::Acute, 1) if own == Quantity::Long && ultima == Quantity::Short => {
+            Some(WarningKind::SoteraRule)
+        }
+        _ => None,
+    }
+}
+
+// Checks the accent of every word of Greek text, returning the character range of each word
+// that breaks a rule.
+pub fn check(s: &str) -> Vec<(WarningKind, Range<usize>)> {
+    syllables::words(s)
+        .iter()
+        .filter_map(|word| {
+            let kind = check_word(word)?;
+            let range = word.ranges[0].start..word.ranges[word.ranges.len() - 1].end;
+            Some((kind, range))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(s: &str) -> Vec<WarningKind> {
+        check(s).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn accepts_correct_accents() {
+        assert!(kinds("ἄνθρωπος ἀνθρώπου ἄνθρωποι σωτῆρα δῶρον λόγοι").is_empty());
+    }
+
+    #[test]
+    fn law_of_limitation() {
+        assert_eq!(kinds("ἄνθρωπου"), [WarningKind::LawOfLimitation]);
+        assert_eq!(kinds("δῶρου"), [WarningKind::LawOfLimitation]);
+        assert_eq!(kinds("ἄπαντας"), []);
+    }
+
+    #[test]
+    fn sotera_rule() {
+        assert_eq!(kinds("δώρον"), [WarningKind::SoteraRule]);
+        // A final α of unknown length breaks no rule.
+        assert_eq!(kinds("σωτήρα"), []);
+        // A final -οι counts as short.
+        assert_eq!(kinds("ἀνθρώποι"), [WarningKind::SoteraRule]);
+    }
+
+    #[test]
+    fn ranges_cover_the_word() {
+        assert_eq!(check("καλὸς δώροι"), [(WarningKind::SoteraRule, 6..11)]);
+    }
+}
@@ -0,0 +1,554 @@
+use crate::scheme::{Diacritic, Key, Scheme};
+use crate::unicode as uc;
+use crate::{accents, graves};
+use crate::{Breathing, Form, Options, Orthography, State, Warning, WarningKind};
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+// Input and output ranges of one glyph. `input_start`..`input_end` and the output range are in
+// chars (Unicode scalar values), so that the two sides can be compared; the input range is also
+// given in UTF-8 bytes, for slicing the input `str`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub input_start: usize,
+    pub input_end: usize,
+    pub input_byte_start: usize,
+    pub input_byte_end: usize,
+    pub output_start: usize,
+    pub output_end: usize,
+}
+
+#[wasm_bindgen]
+pub struct Converter {
+    scheme: Box<dyn Scheme>,
+    options: Options,
+    state: State,
+    buffer: String,
+    // The input character being fed.
+    offset: usize,
+    // Where the glyph being composed starts, if it started before its letter
+    // (e.g. at a Beta Code asterisk).
+    glyph_start: usize,
+    spans: Vec<Span>,
+    input: Vec<u8>,
+    // Byte offset of each input character.
+    starts: Vec<usize>,
+    warnings: Vec<Warning>,
+    // Sigla after a pending sigma, written out once its form is decided.
+    held: String,
+    // Inside escaped text, which is copied as-is.
+    escaped: bool,
+}
+
+#[wasm_bindgen]
+pub struct Conversion {
+    text: String,
+    spans: Vec<Span>,
+    warnings: Vec<Warning>,
+}
+
+#[wasm_bindgen]
+impl Conversion {
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl Converter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Options) -> Self {
+        Self::with_scheme(Box::new(options.convention), options)
+    }
+
+    pub fn feed(&mut self, s: &str) {
+        for &c in s.as_bytes() {
+            // Any byte but a UTF-8 continuation byte starts a new char.
+            if c & 0xc0 != 0x80 {
+                self.offset = self.starts.len();
+                self.starts.push(self.input.len());
+            }
+            self.input.push(c);
+            if self.escape(c) {
+                continue;
+            }
+            if self.options.papyrology && self.sigla(c) {
+                continue;
+            }
+
+            let old = self.state;
+            self.state =
+                self.state
+                    .rewrite_buffer(c, &*self.scheme, &self.options, &mut self.buffer);
+            if self.state.is_initial() {
+                unsafe {
+                    self.buffer.as_mut_vec().push(c);
+                }
+            }
+
+            self.diagnose(old, c);
+            self.map(old, c);
+        }
+    }
+
+    pub fn spans(&self) -> Vec<Span> {
+        self.spans.clone()
+    }
+
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.clone()
+    }
+
+    // Output that no further input can change.
+    pub fn committed(&self) -> String {
+        let pending = self.state.render(&self.options);
+        self.buffer[..self.buffer.len() - pending.len()].to_string()
+    }
+
+    // The glyph still being composed, e.g. a vowel awaiting diacritics or a
+    // sigma whose form depends on the next character.
+    pub fn pending(&self) -> String {
+        // A capital awaiting its letter has nothing to render yet, so its input stands in.
+        if self.state.is_pending_capital() {
+            let start = self.starts[self.glyph_start];
+            return String::from_utf8_lossy(&self.input[start..]).into_owned();
+        }
+        let mut pending = self.state.render(&self.options).into_owned();
+        pending.push_str(&self.held);
+        pending
+    }
+
+    pub fn output(&self) -> String {
+        let mut output = self.buffer.clone();
+        output.push_str(&self.held);
+        output
+    }
+
+    // Ends the input, settling the pending glyph, and returns the whole output.
+    // The converter is reset and can be reused.
+    pub fn finish(&mut self) -> String {
+        self.take().text
+    }
+
+    pub fn convert(&mut self, s: &str) -> Conversion {
+        self.feed(s);
+        self.take()
+    }
+}
+
+impl Converter {
+    pub fn with_scheme(scheme: Box<dyn Scheme>, options: Options) -> Self {
+        Self {
+            scheme,
+            options,
+            state: State::initial(),
+            buffer: String::new(),
+            offset: 0,
+            glyph_start: 0,
+            spans: Vec::new(),
+            input: Vec::new(),
+            starts: Vec::new(),
+            warnings: Vec::new(),
+            held: String::new(),
+            escaped: false,
+        }
+    }
+
+    fn take(&mut self) -> Conversion {
+        if self.state.is_pending_capital() {
+            self.drop_capital(self.starts.len());
+        }
+        self.flush();
+        // Monotonic text has no grave to adjust.
+        if self.options.adjust_graves && self.options.orthography != Orthography::Monotonic {
+            self.buffer = graves::adjust(&self.buffer, &self.options);
+        }
+        if self.options.check_accents {
+            self.check_accents();
+        }
+        self.offset = 0;
+        self.input.clear();
+        self.starts.clear();
+        self.escaped = false;
+
+        Conversion {
+            text: std::mem::take(&mut self.buffer),
+            spans: std::mem::take(&mut self.spans),
+            warnings: std::mem::take(&mut self.warnings),
+        }
+    }
+
+    // Settles the pending glyph as at the end of a word.
+    fn flush(&mut self) {
+        self.settle(self.state);
+        self.state.finish(&self.options, &mut self.buffer);
+        self.buffer.push_str(&std::mem::take(&mut self.held));
+        self.state = State::initial();
+    }
+
+    // Handles the escape delimiters and the text between them, and returns whether `c` was
+    // either. The delimiters themselves are not copied.
+    fn escape(&mut self, c: u8) -> bool {
+        let is = |delimiter: Option<char>| c.is_ascii() && delimiter == Some(c as char);
+
+        if self.escaped {
+            if is(self.options.escape_close) {
+                self.escaped = false;
+            } else {
+                unsafe {
+                    self.buffer.as_mut_vec().push(c);
+                }
+                self.map(State::initial(), c);
+            }
+            return true;
+        }
+
+        if is(self.options.escape_open) {
+            if self.state.is_pending_capital() {
+                self.drop_capital(self.offset);
+            }
+            self.flush();
+            self.escaped = true;
+            return true;
+        }
+
+        false
+    }
+
+    // Handles the papyrological sigla and returns whether `c` was one of them. Brackets after
+    // a sigma are held back, so that the sigma stays medial if the word goes on past them.
+    fn sigla(&mut self, c: u8) -> bool {
+        let i = self.offset;
+        let output_end = self.spans.last().map_or(0, |span| span.output_end);
+
+        if self.state.is_pending_sigma() && self.scheme.bracket(c) {
+            self.held.push(c as char);
+            self.spans.push(Span {
+                input_start: i,
+                input_end: i + 1,
+                input_byte_start: self.starts[i],
+                input_byte_end: self.input.len(),
+                output_start: output_end,
+                output_end: output_end + 1,
+            });
+            return true;
+        }
+
+        if !self.held.is_empty() {
+            if matches!(self.scheme.key(c), Key::Other | Key::Punctuation(_)) {
+                self.state.finish(&self.options, &mut self.buffer);
+            }
+            self.buffer.push_str(&std::mem::take(&mut self.held));
+            self.state = State::initial();
+        }
+
+        if !self.scheme.underdot(c) || !(self.state.is_vowel() || self.state.is_consonant()) {
+            return false;
+        }
+
+        self.settle(self.state);
+        if self.state.is_pending_sigma() {
+            self.held.push_str(uc::UNDERDOT);
+        } else {
+            self.state.underdot(&self.options, &mut self.buffer);
+            self.state = State::initial();
+        }
+        if let Some(span) = self.spans.last_mut() {
+            span.input_end = i + 1;
+            span.input_byte_end = self.input.len();
+            span.output_end += 1;
+        }
+
+        true
+    }
+
+    // Warns about each word of the output that breaks an accent rule, at the input of the
+    // whole word.
+    fn check_accents(&mut self) {
+        for (kind, word) in accents::check(&self.buffer) {
+            let mut spans = self
+                .spans
+                .iter()
+                .filter(|span| span.output_start < word.end && word.start < span.output_end);
+            let Some(&first) = spans.next() else {
+                continue;
+            };
+            let last = spans.next_back().unwrap_or(&first);
+
+            let glyph = first.input_start..last.input_end;
+            self.warn(kind, first.input_start, glyph);
+        }
+    }
+
+    fn warn(&mut self, kind: WarningKind, position: usize, glyph: Range<usize>) {
+        let end = self
+            .starts
+            .get(glyph.end)
+            .map_or(self.input.len(), |&end| end);
+        let bytes = self.starts[glyph.start]..end;
+        let sequence = String::from_utf8_lossy(&self.input[bytes]).into_owned();
+        self.warnings.push(Warning::new(kind, position, sequence));
+    }
+
+    // Called before `map`, so the last span is still the one of the glyph `old`.
+    fn diagnose(&mut self, old: State, c: u8) {
+        let key = self.scheme.key(c);
+        let Key::Diacritic(diacritic) = key else {
+            let capital = self.state.is_vowel()
+                || self.state.is_consonant()
+                || self.state.is_pending_capital()
+                || matches!(self.state, State::Symbol(_));
+            if old.is_pending_capital() && !capital {
+                self.drop_capital(self.offset);
+            }
+            self.settle(old);
+            return;
+        };
+        // Monotonic text drops these on purpose.
+        if self.options.orthography == Orthography::Monotonic
+            && matches!(
+                diacritic,
+                Diacritic::Smooth | Diacritic::Rough | Diacritic::Iota
+            )
+        {
+            return;
+        }
+        if old.is_initial() {
+            self.warn(
+                WarningKind::DiacriticWithoutLetter,
+                self.offset,
+                self.offset..self.offset + 1,
+            );
+            return;
+        }
+        if old.breathing() == Breathing::Koronis
+            && matches!(diacritic, Diacritic::Smooth | Diacritic::Rough)
+        {
+            let start = self
+                .spans
+                .last()
+                .map_or(self.offset, |span| span.input_start);
+            self.warn(
+                WarningKind::BreathingAfterCrasis,
+                self.offset,
+                start..self.offset + 1,
+            );
+            return;
+        }
+        if old != self.state || old.is_pending_capital() {
+            return;
+        }
+
+        let kind = match diacritic {
+            Diacritic::Circumflex if old.is_vowel() => WarningKind::CircumflexOnShortVowel,
+            _ if old.is_consonant() => WarningKind::DiacriticOnConsonant,
+            _ => WarningKind::UnsupportedDiacritic,
+        };
+        let start = self
+            .spans
+            .last()
+            .map_or(self.offset, |span| span.input_start);
+        self.warn(kind, self.offset, start..self.offset + 1);
+    }
+
+    // Warns about an asterisk, and any marks typed after it, that no letter followed. They
+    // are dropped.
+    fn drop_capital(&mut self, end: usize) {
+        let glyph = self.glyph_start..end;
+        self.warn(WarningKind::DiacriticWithoutLetter, self.glyph_start, glyph);
+    }
+
+    // Checks the glyph `old` once no more diacritics can be added to it.
+    fn settle(&mut self, old: State) {
+        // Combining sequences are exact in the normalized forms.
+        if self.options.form != Form::Precomposed {
+            return;
+        }
+        // All capitals often leave marks without a precomposed capital, which is expected; the
+        // glyph is judged in the case it was typed.
+        let options = Options {
+            all_caps: false,
+            ..self.options
+        };
+        if old.is_initial() || old.is_pending_capital() || old.renders_precomposed(&options) {
+            return;
+        }
+        // Quantity marks never share a code point with other marks, so there is nothing to
+        // point out.
+        if old.has_quantity() {
+            return;
+        }
+
+        if let Some(&span) = self.spans.last() {
+            let glyph = span.input_start..span.input_end;
+            self.warn(WarningKind::NoPrecomposedForm, span.input_start, glyph);
+        }
+    }
+
+    // Records where the byte `c`, of the char at `self.offset`, landed in the output.
+    fn map(&mut self, old: State, c: u8) {
+        let i = self.offset;
+        // The glyph before may have given up its koronis, and a combining mark with it.
+        if old.passes_koronis(self.state) {
+            let bare = old.without_koronis().render(&self.options).chars().count();
+            if let Some(span) = self.spans.last_mut() {
+                span.output_end = span.output_start + bare;
+            }
+        }
+        let output_end = self.spans.last().map_or(0, |span| span.output_end);
+
+        if self.state.is_initial() {
+            // Continuation bytes of a UTF-8 sequence belong to the char copied at its lead byte.
+            if c & 0xc0 == 0x80 {
+                if let Some(span) = self.spans.last_mut() {
+                    span.input_end = i + 1;
+                    span.input_byte_end = self.input.len();
+                }
+            } else {
+                self.spans.push(Span {
+                    input_start: i,
+                    input_end: i + 1,
+                    input_byte_start: self.starts[i],
+                    input_byte_end: self.input.len(),
+                    output_start: output_end,
+                    output_end: output_end + 1,
+                });
+            }
+            return;
+        }
+
+        let glyph_len = self.state.render(&self.options).chars().count();
+        match (old.is_pending_capital(), self.state.is_pending_capital()) {
+            (true, true) => {}
+            (false, true) => self.glyph_start = i,
+            (pending, false) => {
+                let extends = old.continues(c, &*self.scheme);
+                let start = if pending { self.glyph_start } else { i };
+
+                match self.spans.last_mut() {
+                    Some(span) if extends => {
+                        span.input_end = i + 1;
+                        span.input_byte_end = self.input.len();
+                        span.output_end = span.output_start + glyph_len;
+                    }
+                    _ => self.spans.push(Span {
+                        input_start: start,
+                        input_end: i + 1,
+                        input_byte_start: self.starts[start],
+                        input_byte_end: self.input.len(),
+                        output_start: output_end,
+                        output_end: output_end + glyph_len,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Convention;
+
+    fn convert(s: &str, options: Options) -> Conversion {
+        Converter::new(options).convert(s)
+    }
+
+    #[test]
+    fn koronis_leaves_a_longer_capital() {
+        let conversion = convert("k'A=y", Options::default());
+        let text: Vec<char> = conversion.text().chars().collect();
+        let last = conversion.spans().last().copied().unwrap();
+        assert_eq!(last.output_end, text.len());
+        for pair in conversion.spans().windows(2) {
+            assert_eq!(pair[0].output_end, pair[1].output_start);
+        }
+    }
+
+    #[test]
+    fn elision_keeps_the_apostrophe() {
+        let monotonic = Options {
+            orthography: Orthography::Monotonic,
+            ..Options::default()
+        };
+        for (latin, options, greek) in [
+            ("s'agapw/", monotonic, "σ’αγαπώ"),
+            ("s'a", Options::default(), "σ’α"),
+            ("k'agw/", Options::default(), "κἀγώ"),
+        ] {
+            let conversion = convert(latin, options);
+            assert_eq!(conversion.text(), greek);
+            assert!(conversion.warnings().is_empty());
+        }
+
+        let kinds: Vec<WarningKind> = convert("d'a)/n", Options::default())
+            .warnings()
+            .iter()
+            .map(|warning| warning.kind())
+            .collect();
+        assert_eq!(kinds, [WarningKind::BreathingAfterCrasis]);
+    }
+
+    #[test]
+    fn all_caps_writes_the_iota_out() {
+        let all_caps = Options {
+            all_caps: true,
+            ..Options::default()
+        };
+        for (latin, greek) in [("a|", "ΑΙ"), ("tw=|", "ΤΩ\u{342}Ι")] {
+            let conversion = convert(latin, all_caps);
+            assert_eq!(conversion.text(), greek);
+            assert!(conversion.warnings().is_empty());
+        }
+    }
+
+    #[test]
+    fn asterisk_without_letter() {
+        let beta_code = Options {
+            convention: Convention::BetaCode,
+            ..Options::default()
+        };
+        for latin in ["*) A", "*)"] {
+            let warnings = convert(latin, beta_code).warnings();
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].kind(), WarningKind::DiacriticWithoutLetter);
+            assert_eq!(warnings[0].sequence(), "*)");
+        }
+
+        let mut converter = Converter::new(beta_code);
+        converter.feed("a *)");
+        assert_eq!(converter.pending(), "*)");
+    }
+
+    #[test]
+    fn monotonic_has_no_koronis() {
+        let monotonic = Options {
+            orthography: Orthography::Monotonic,
+            ..Options::default()
+        };
+        assert_eq!(convert("to' 'legan", monotonic).text(), "το’ ’λεγαν");
+    }
+
+    #[test]
+    fn spans_give_chars_and_bytes() {
+        let conversion = convert("aéb", Options::default());
+        let span = conversion.spans()[1];
+        assert_eq!((span.input_start, span.input_end), (1, 2));
+        assert_eq!((span.input_byte_start, span.input_byte_end), (1, 3));
+        assert_eq!((span.output_start, span.output_end), (1, 2));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Warning, WarningKind};
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorReason {
+    UnknownDiacriticTarget,
+    DroppedMark,
+    UnsupportedCombination,
+    MisplacedAccent,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ConversionError {
+    offset: usize,
+    reason: ErrorReason,
+    sequence: String,
+}
+
+#[wasm_bindgen]
+impl ConversionError {
+    // Input char offset of the offending character, as for `Span`.
+    #[wasm_bindgen(getter)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn reason(&self) -> ErrorReason {
+        self.reason
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sequence(&self) -> String {
+        self.sequence.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<&Warning> for ConversionError {
+    fn from(warning: &Warning) -> Self {
+        let reason = match warning.kind() {
+            WarningKind::DiacriticWithoutLetter => ErrorReason::UnknownDiacriticTarget,
+            WarningKind::CircumflexOnShortVowel
+            | WarningKind::DiacriticOnConsonant
+            | WarningKind::UnsupportedDiacritic
+            | WarningKind::BreathingAfterCrasis => ErrorReason::DroppedMark,
+            WarningKind::NoPrecomposedForm => ErrorReason::UnsupportedCombination,
+            WarningKind::LawOfLimitation
+            | WarningKind::CircumflexOnShortSyllable
+            | WarningKind::SoteraRule => ErrorReason::MisplacedAccent,
+        };
+
+        Self {
+            offset: warning.position(),
+            reason,
+            sequence: warning.sequence(),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            ErrorReason::UnknownDiacriticTarget => "diacritic without a letter",
+            ErrorReason::DroppedMark => "diacritic cannot be placed on the letter",
+            ErrorReason::UnsupportedCombination => "combination has no precomposed form",
+            ErrorReason::MisplacedAccent => "accent is misplaced for the syllable lengths",
+        };
+
+        write!(f, "{reason} at offset {}: {:?}", self.offset, self.sequence)
+    }
+}
+
+impl std::error::Error for ConversionError {}
@@ -0,0 +1,143 @@
+use crate::scheme::Diacritic;
+use crate::state;
+use crate::syllables::{self, Word};
+use crate::unicode as uc;
+use crate::{Accent, Options};
+
+use std::ops::Range;
+
+// Words that lean on the one before them, written without an accent. An oxytone keeps its acute
+// before them.
+const ENCLITICS: &[&str] = &[
+    "μου",
+    "μοι",
+    "με",
+    "σου",
+    "σοι",
+    "σε",
+    "τις",
+    "τι",
+    "τινος",
+    "τινι",
+    "τινα",
+    "τινες",
+    "τινων",
+    "τισι",
+    "τισιν",
+    "τινας",
+    "του",
+    "τω",
+    "που",
+    "ποι",
+    "πη",
+    "πως",
+    "ποθι",
+    "ποθεν",
+    "ποτε",
+    "πω",
+    "γε",
+    "τε",
+    "τοι",
+    "περ",
+    "νυ",
+    "νυν",
+    "κε",
+    "κεν",
+    "ρα",
+    "ειμι",
+    "εστι",
+    "εστιν",
+    "εσμεν",
+    "εστε",
+    "εισι",
+    "εισιν",
+    "φημι",
+    "φησι",
+    "φησιν",
+    "φαμεν",
+    "φατε",
+    "φασι",
+    "φασιν",
+];
+
+fn is_enclitic(word: &Word) -> bool {
+    if word
+        .letters
+        .iter()
+        .any(|state| state.accent() != Accent::None)
+    {
+        return false;
+    }
+
+    let spelling: String = word
+        .letters
+        .iter()
+        .map(|state| state.bare().to_str())
+        .collect();
+    ENCLITICS
+        .iter()
+        .any(|enclitic| enclitic.replace('ς', "σ") == spelling)
+}
+
+// The accent the ultima should have given the word after it, if any.
+fn accent_before(next: Option<&Word>) -> Accent {
+    match next {
+        Some(word) if is_enclitic(word) => Accent::Acute,
+        Some(_) => Accent::Grave,
+        None => Accent::Acute,
+    }
+}
+
+// Turns the acute of each oxytone into a grave before another word, and a grave back into an
+// acute before punctuation, an enclitic or the end of a line. Rewritten letters are rendered
+// with `options`, as the rest of the text was.
+pub fn adjust(s: &str, options: &Options) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let words = syllables::words(s);
+    let mut rewrites: Vec<(Range<usize>, String)> = Vec::new();
+
+    for (i, word) in words.iter().enumerate() {
+        let Some(ultima) = syllables::nuclei(&word.letters).pop() else {
+            continue;
+        };
+        let Some(j) = ultima
+            .clone()
+            .find(|&j| matches!(word.letters[j].accent(), Accent::Acute | Accent::Grave))
+        else {
+            continue;
+        };
+
+        // Only spaces may stand between the word and the next one.
+        let end = word.ranges[word.ranges.len() - 1].end;
+        let gap = chars[end..]
+            .iter()
+            .take_while(|&&c| c.is_whitespace() && c != '\n')
+            .count();
+        let next = words
+            .get(i + 1)
+            .filter(|next| next.ranges[0].start == end + gap);
+
+        let old = word.letters[j];
+        let new = match (old.accent(), accent_before(next)) {
+            (Accent::Acute, Accent::Grave) => state::toggle(old, Diacritic::Grave),
+            (Accent::Grave, Accent::Acute) => state::toggle(old, Diacritic::Acute),
+            _ => continue,
+        };
+
+        // An underdot is not part of the state, so it is put back. Spans into the text stay
+        // valid as long as the glyph keeps its length.
+        let range = word.ranges[j].clone();
+        let mut glyph = new.render(options).into_owned();
+        if chars[range.clone()]
+            .iter()
+            .any(|&c| uc::UNDERDOT.starts_with(c))
+        {
+            new.underdot(options, &mut glyph);
+        }
+        if glyph.chars().count() == range.len() {
+            rewrites.push((range, glyph));
+        }
+    }
+
+    state::rewrite(s, rewrites)
+}
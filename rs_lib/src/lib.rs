@@ -3,22 +3,53 @@ mod unicode;
 mod state;
 use state::State;
 
+mod converter;
+pub use converter::{Conversion, Converter, Span};
+
+mod reverse;
+
+mod numeral;
+
+mod syllables;
+pub use syllables::Syllable;
+
+mod accents;
+
+mod graves;
+
+mod recessive;
+
+mod scansion;
+pub use scansion::{Caesura, CaesuraKind, Mark, MetricalSyllable, Scansion};
+
+mod warning;
+pub use warning::{Warning, WarningKind};
+
+mod error;
+pub use error::{ConversionError, ErrorReason};
+
+mod scheme;
+pub use scheme::{
+    BetaCode, Diacritic, Key, Latin, Letter, Perseus, Punctuation, Scheme, SigmaForm,
+};
+
 use wasm_bindgen::prelude::*;
 
-#[derive(Debug, Clone, Copy)]
-enum Case {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
     Capital,
     Small,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Breathing {
     None,
     Smooth,
     Rough,
+    Koronis,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Accent {
     None,
     Grave,
@@ -26,31 +57,220 @@ enum Accent {
     Circumflex,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Subscript {
     None,
     Iota,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Diaeresis {
     None,
     Some,
 }
 
+// Vowel length marked on α, ι and υ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quantity {
+    None,
+    Long,
+    Short,
+}
+
 #[wasm_bindgen]
-pub fn latin_to_greek(s: &str) -> String {
-    let mut buffer = String::new();
-
-    let mut state = State::initial();
-    for &c in s.as_bytes() {
-        state = state.rewrite_buffer(c, &mut buffer);
-        if state.is_initial() {
-            unsafe {
-                buffer.as_mut_vec().push(c);
-            }
+#[derive(Debug, Clone, Copy)]
+pub enum Convention {
+    Latin,
+    Perseus,
+    BetaCode,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    // As listed in the tables: precomposed where possible, the base letter and combining marks
+    // otherwise.
+    Precomposed,
+    Nfc,
+    Nfd,
+}
+
+// Code points for acute-accented vowels in the precomposed form. The normalized forms always
+// use tonos, which is what the oxia code points normalize to.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcuteForm {
+    // Greek and Coptic block, e.g. U+03AC
+    Tonos,
+    // Greek Extended block, e.g. U+1F71
+    Oxia,
+}
+
+// Code point for the ano teleia (`:`). The normalized forms always use the middle dot, which is
+// what U+0387 normalizes to; likewise they use the semicolon for the question mark.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnoTeleiaForm {
+    // U+0387
+    AnoTeleia,
+    // U+00B7
+    MiddleDot,
+}
+
+// Code point for the elision apostrophe.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApostropheForm {
+    // U+2019
+    Quotation,
+    // U+02BC
+    ModifierLetter,
+}
+
+// How an iota subscript is written.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IotaForm {
+    // Below a small letter, or beside a capital as a prosgegrammeni
+    Subscript,
+    // As a letter after the vowel, e.g. αι and Αι, or ΑΙ in all capitals
+    Adscript,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orthography {
+    Polytonic,
+    Monotonic,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub convention: Convention,
+    pub form: Form,
+    pub acute: AcuteForm,
+    pub orthography: Orthography,
+    // Renders every sigma as lunate, regardless of its position.
+    pub lunate_sigma: bool,
+    // Reads papyrological sigla: underdots on uncertain letters, and brackets that may sit
+    // inside a word.
+    pub papyrology: bool,
+    // Renders `:`, `;` and elision apostrophes as Greek punctuation instead of copying them.
+    pub punctuation: bool,
+    pub ano_teleia: AnoTeleiaForm,
+    pub apostrophe: ApostropheForm,
+    pub small_iota: IotaForm,
+    pub capital_iota: IotaForm,
+    // Renders every letter as a capital, keeping its diacritics. An iota subscript is written
+    // out after the vowel, whatever `capital_iota` says.
+    pub all_caps: bool,
+    // ASCII delimiters around text to be copied as-is, like the `&` and `$` font shifts of
+    // Beta Code. The same character for both makes a toggle.
+    pub escape_open: Option<char>,
+    pub escape_close: Option<char>,
+    // Warns about words whose accent breaks the law of limitation, the placement of the
+    // circumflex or the σωτῆρα rule.
+    pub check_accents: bool,
+    // Once the input ends, gives each oxytone a grave before another word and an acute before
+    // punctuation, an enclitic or the end of a line. Monotonic text is left as it is.
+    pub adjust_graves: bool,
+}
+
+#[wasm_bindgen]
+impl Options {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            convention: Convention::Latin,
+            form: Form::Precomposed,
+            acute: AcuteForm::Tonos,
+            orthography: Orthography::Polytonic,
+            lunate_sigma: false,
+            papyrology: false,
+            punctuation: true,
+            ano_teleia: AnoTeleiaForm::AnoTeleia,
+            apostrophe: ApostropheForm::Quotation,
+            small_iota: IotaForm::Subscript,
+            capital_iota: IotaForm::Subscript,
+            all_caps: false,
+            escape_open: None,
+            escape_close: None,
+            check_accents: false,
+            adjust_graves: false,
         }
     }
+}
+
+#[wasm_bindgen]
+pub fn latin_to_greek(s: &str) -> String {
+    to_greek(s, Convention::Latin)
+}
+
+#[wasm_bindgen]
+pub fn to_greek(s: &str, convention: Convention) -> String {
+    let mut converter = Converter::new(Options {
+        convention,
+        ..Options::default()
+    });
+    converter.feed(s);
+    converter.finish()
+}
+
+#[wasm_bindgen]
+pub fn to_greek_with_spans(s: &str, options: Options) -> Conversion {
+    Converter::new(options).convert(s)
+}
+
+// Fails at the first input that would otherwise be rendered lossily.
+#[wasm_bindgen]
+pub fn try_latin_to_greek(s: &str, options: Options) -> Result<String, ConversionError> {
+    let conversion = Converter::new(options).convert(s);
+
+    match conversion.warnings().iter().min_by_key(|w| w.position()) {
+        Some(warning) => Err(ConversionError::from(warning)),
+        None => Ok(conversion.text()),
+    }
+}
+
+#[wasm_bindgen]
+pub fn greek_to_latin(s: &str) -> String {
+    reverse::greek_to_latin(s)
+}
+
+// E.g. 1821 to ͵αωκαʹ. Only 1 to 999999 can be written.
+#[wasm_bindgen]
+pub fn to_greek_numeral(n: u32) -> Option<String> {
+    numeral::to_greek(n)
+}
 
-    buffer
+#[wasm_bindgen]
+pub fn parse_greek_numeral(s: &str) -> Option<u32> {
+    numeral::parse(s)
+}
+
+// E.g. `paideyomen` to παιδεύομεν. An α, ι or υ in the last two syllables counts as short
+// unless typed with a macron.
+#[wasm_bindgen]
+pub fn place_recessive_accent(s: &str, options: Options) -> String {
+    recessive::place(s, &options)
+}
+
+// Scans a line of Greek text as a dactylic hexameter. Every scansion the syllable weights allow
+// is returned, so there may be several, or none if the line does not scan.
+#[wasm_bindgen]
+pub fn scan_hexameter(s: &str) -> Vec<Scansion> {
+    scansion::scan(s)
+}
+
+// Splits every word of Greek text into syllables.
+#[wasm_bindgen]
+pub fn syllabify(s: &str) -> Vec<Syllable> {
+    syllables::syllabify(s)
 }
@@ -0,0 +1,137 @@
+use crate::state::letter;
+use crate::unicode as uc;
+use crate::{Case, Letter};
+
+use Letter::*;
+
+// The letters for 1-9, 10-90 and 100-900.
+const DIGITS: [[Letter; 9]; 3] = [
+    [Alpha, Beta, Gamma, Delta, Epsilon, Stigma, Zeta, Eta, Theta],
+    [Iota, Kappa, Lambda, Mu, Nu, Xi, Omicron, Pi, NumeralKoppa],
+    [Rho, Sigma, Tau, Ypsilon, Phi, Chi, Psi, Omega, Sampi],
+];
+
+// Older or alternative forms also found as numerals.
+const VARIANTS: &[(char, u32)] = &[
+    ('\u{03c2}', 6),   // final sigma, standing in for stigma
+    ('\u{03f2}', 200), // lunate sigma
+    ('\u{03f9}', 200),
+    ('\u{03dd}', 6), // digamma
+    ('\u{03dc}', 6),
+    ('\u{03d9}', 90), // archaic koppa
+    ('\u{03d8}', 90),
+];
+
+fn digit(l: Letter, case: Case) -> char {
+    letter(l, case).to_str().chars().next().unwrap_or_default()
+}
+
+// Digits of 1-999, from the hundreds down.
+fn push_digits(buffer: &mut String, n: u32, thousands: bool) {
+    for (place, digits) in DIGITS.iter().enumerate().rev() {
+        let d = n / 10u32.pow(place as u32) % 10;
+        if d == 0 {
+            continue;
+        }
+        if thousands {
+            buffer.push_str(uc::LOWER_KERAIA);
+        }
+        buffer.push(digit(digits[d as usize - 1], Case::Small));
+    }
+}
+
+pub fn to_greek(n: u32) -> Option<String> {
+    if !(1..1_000_000).contains(&n) {
+        return None;
+    }
+
+    let mut buffer = String::new();
+    push_digits(&mut buffer, n / 1000, true);
+    push_digits(&mut buffer, n % 1000, false);
+    buffer.push_str(uc::KERAIA);
+
+    Some(buffer)
+}
+
+fn value(c: char) -> Option<u32> {
+    for (place, digits) in DIGITS.iter().enumerate() {
+        for (i, &l) in digits.iter().enumerate() {
+            if c == digit(l, Case::Small) || c == digit(l, Case::Capital) {
+                return Some((i as u32 + 1) * 10u32.pow(place as u32));
+            }
+        }
+    }
+
+    VARIANTS.iter().find(|(v, _)| *v == c).map(|&(_, n)| n)
+}
+
+// Reads a numeral with or without its keraia. A lower keraia multiplies the next letter
+// by 1000.
+pub fn parse(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let s = [uc::KERAIA, uc::PRIME, "'"]
+        .iter()
+        .find_map(|keraia| s.strip_suffix(keraia))
+        .unwrap_or(s);
+
+    let mut n: u32 = 0;
+    let mut thousands = false;
+    // The power of ten of the last digit; each digit must stand in a lower place.
+    let mut place = None;
+    for c in s.chars() {
+        if uc::LOWER_KERAIA.starts_with(c) {
+            if thousands {
+                return None;
+            }
+            thousands = true;
+            continue;
+        }
+
+        let v = value(c)?;
+        let v = if thousands { v.checked_mul(1000)? } else { v };
+        if place.is_some_and(|place| v >= place) {
+            return None;
+        }
+        place = Some(10u32.pow(v.ilog10()));
+        n = n.checked_add(v)?;
+        thousands = false;
+    }
+
+    if n == 0 || thousands {
+        None
+    } else {
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for n in [1, 6, 9, 10, 99, 123, 999, 1000, 1500, 999_999] {
+            assert_eq!(to_greek(n).and_then(|numeral| parse(&numeral)), Some(n));
+        }
+        assert_eq!(to_greek(0), None);
+        assert_eq!(to_greek(1_000_000), None);
+    }
+
+    #[test]
+    fn final_sigma_is_stigma() {
+        assert_eq!(parse("ςʹ"), Some(6));
+        assert_eq!(parse("ρςʹ"), Some(106));
+    }
+
+    #[test]
+    fn rejects_misordered_places() {
+        for numeral in ["βι", "αα", "ααʹ", "αβγ", "͵͵α", "γρʹ"] {
+            assert_eq!(parse(numeral), None, "{numeral}");
+        }
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse(&"͵ϡ".repeat(5000)), None);
+    }
+}
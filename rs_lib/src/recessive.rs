@@ -0,0 +1,61 @@
+use crate::accents;
+use crate::scheme::Diacritic;
+use crate::state;
+use crate::syllables::{self, Word};
+use crate::{Accent, Converter, Options, Quantity};
+
+use std::ops::Range;
+
+// A syllable of unknown length counts as short, as in most verb endings. Marking the vowel
+// with a macron or a breve settles it.
+fn is_long(word: &Word, nucleus: &Range<usize>) -> bool {
+    accents::length(word, nucleus) == Quantity::Long
+}
+
+// The syllable that takes the accent, counted from the start, and the accent.
+fn recessive(word: &Word, nuclei: &[Range<usize>]) -> Option<(usize, Diacritic)> {
+    let ultima = nuclei.len().checked_sub(1)?;
+    let long_ultima = is_long(word, &nuclei[ultima]);
+    let i = match long_ultima {
+        true => ultima.saturating_sub(1),
+        false => ultima.saturating_sub(2),
+    };
+
+    // A long vowel takes a circumflex on the ultima, or on the penult before a short ultima.
+    let circumflex =
+        is_long(word, &nuclei[i]) && (i == ultima || (i + 1 == ultima && !long_ultima));
+    match circumflex {
+        true => Some((i, Diacritic::Circumflex)),
+        false => Some((i, Diacritic::Acute)),
+    }
+}
+
+// Converts the input and accents each word without an accent as far back as its ultima
+// allows, e.g. `paideyomen` to παιδεύομεν. The accent goes on the second vowel of a diphthong.
+pub fn place(s: &str, options: &Options) -> String {
+    let greek = Converter::new(*options).convert(s).text();
+    let mut rewrites = Vec::new();
+
+    for word in syllables::words(&greek) {
+        if word
+            .letters
+            .iter()
+            .any(|state| state.accent() != Accent::None)
+        {
+            continue;
+        }
+        let nuclei = syllables::nuclei(&word.letters);
+        let Some((i, accent)) = recessive(&word, &nuclei) else {
+            continue;
+        };
+
+        let j = nuclei[i].end - 1;
+        let accented = state::toggle(word.letters[j], accent);
+        rewrites.push((
+            word.ranges[j].clone(),
+            accented.render(options).into_owned(),
+        ));
+    }
+
+    state::rewrite(&greek, rewrites)
+}
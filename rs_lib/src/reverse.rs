@@ -0,0 +1,181 @@
+use crate::unicode as uc;
+use crate::{AcuteForm, Case, Converter, Options};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+const LETTERS: &[&str] = &[
+    "a", "b", "g", "d", "e", "z", "h", "q", "i", "k", "l", "m", "n", "x", "o", "p", "r", "s", "t",
+    "y", "f", "c", "j", "w", "v", "#h", "#q", "#k", "#s", "#p", "A", "B", "G", "D", "E", "Z", "H",
+    "Q", "I", "K", "L", "M", "N", "X", "O", "P", "R", "S", "T", "Y", "F", "C", "J", "W", "V", "#H",
+    "#Q", "#K", "#S", "#P", "s3", "S3", "'",
+];
+
+const BREATHINGS: &[&str] = &["", ")", "("];
+const ACCENTS: &[&str] = &["", "\\", "/", "="];
+const EXTRAS: &[&str] = &["", "|", "\"", "_", "^"];
+
+// (greek, latin) pairs for every glyph the tables can produce. When two
+// latin sequences render the same glyph, the one listed first (i.e. the one
+// with fewer diacritics) wins.
+fn glyphs() -> Vec<(String, String)> {
+    let mut glyphs: Vec<(String, String)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    let tonos = Options::default();
+    let oxia = Options {
+        acute: AcuteForm::Oxia,
+        ..tonos
+    };
+
+    for letter in LETTERS {
+        for breathing in BREATHINGS {
+            for accent in ACCENTS {
+                for extra in EXTRAS {
+                    let mut latin = letter.to_string();
+                    latin.push_str(breathing);
+                    latin.push_str(accent);
+                    latin.push_str(extra);
+
+                    for options in [tonos, oxia] {
+                        // Not finished, so that a sigma stays medial.
+                        let mut converter = Converter::new(options);
+                        converter.feed(&latin);
+                        let greek = converter.output();
+
+                        if seen.insert(greek.clone()) {
+                            glyphs.push((greek, latin.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    glyphs.push((uc::FinalSigma[Case::Small].to_string(), "s".to_string()));
+    for (greek, latin) in [
+        (uc::ANO_TELEIA, ":"),
+        (uc::MIDDLE_DOT, ":"),
+        (uc::QUESTION_MARK, ";"),
+        (uc::APOSTROPHE, "'"),
+        (uc::MODIFIER_APOSTROPHE, "'"),
+        (uc::KERAIA, "#"),
+        (uc::PRIME, "#"),
+    ] {
+        glyphs.push((greek.to_string(), latin.to_string()));
+    }
+
+    glyphs
+}
+
+// How a sigma typed right before each printable ASCII character comes out: medial, final, or
+// `None` when the character picks the form itself, as `1`, `2` and `3` do. Anything else ends
+// the word.
+fn sigma_forms() -> HashMap<u8, Option<char>> {
+    (0x20..0x7f)
+        .map(|b| {
+            let latin = format!("s{}", b as char);
+            let greek = Converter::new(Options::default()).convert(&latin).text();
+            let mut chars = greek.chars();
+            let sigma = chars.next();
+            (b, sigma.filter(|_| chars.next().is_some()))
+        })
+        .collect()
+}
+
+// The glyphs by their first character, longest first.
+fn index() -> HashMap<char, Vec<(String, String)>> {
+    let mut index: HashMap<char, Vec<(String, String)>> = HashMap::new();
+    for (greek, latin) in glyphs() {
+        if let Some(c) = greek.chars().next() {
+            index.entry(c).or_default().push((greek, latin));
+        }
+    }
+    for glyphs in index.values_mut() {
+        glyphs.sort_by_key(|(greek, _)| std::cmp::Reverse(greek.len()));
+    }
+
+    index
+}
+
+pub fn greek_to_latin(s: &str) -> String {
+    static INDEX: OnceLock<HashMap<char, Vec<(String, String)>>> = OnceLock::new();
+    static SIGMA_FORMS: OnceLock<HashMap<u8, Option<char>>> = OnceLock::new();
+    let index = INDEX.get_or_init(index);
+    let sigma_forms = SIGMA_FORMS.get_or_init(sigma_forms);
+
+    // (greek, latin) pieces, so that a sigma can see what comes after it.
+    let mut pieces: Vec<(&str, &str)> = Vec::new();
+    let mut rest = s;
+
+    while let Some(c) = rest.chars().next() {
+        let longest = index.get(&c).and_then(|glyphs| {
+            glyphs
+                .iter()
+                .find(|(greek, _)| rest.starts_with(greek.as_str()))
+        });
+
+        let len = match longest {
+            Some((greek, latin)) => {
+                pieces.push((greek, latin));
+                greek.len()
+            }
+            None => {
+                let len = c.len_utf8();
+                pieces.push((&rest[..len], &rest[..len]));
+                len
+            }
+        };
+        rest = &rest[len..];
+    }
+
+    let medial = uc::Sigma[Case::Small];
+    let final_sigma = uc::FinalSigma[Case::Small];
+    let mut buffer = String::new();
+    for (i, &(greek, latin)) in pieces.iter().enumerate() {
+        buffer.push_str(latin);
+        if greek != medial && greek != final_sigma {
+            continue;
+        }
+
+        // A plain `s` takes the form its position implies; otherwise the form is spelled out.
+        let next = pieces
+            .get(i + 1)
+            .and_then(|(_, latin)| latin.bytes().next());
+        let implied = match next.and_then(|b| sigma_forms.get(&b)) {
+            Some(&form) => form,
+            None => final_sigma.chars().next(),
+        };
+        if implied != greek.chars().next() {
+            buffer.push(if greek == medial { '1' } else { '2' });
+        }
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(greek: &str) -> String {
+        let latin = greek_to_latin(greek);
+        Converter::new(Options::default()).convert(&latin).text()
+    }
+
+    #[test]
+    fn sigma_keeps_its_form() {
+        for greek in [
+            "λόγος",
+            "λόγοσ",
+            "σα",
+            "ςα",
+            "ὁ λόγος, καὶ",
+            "λόγοσ καὶ",
+            "σ1",
+            "ς2",
+        ] {
+            assert_eq!(round_trip(greek), greek);
+        }
+    }
+}
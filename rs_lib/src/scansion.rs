@@ -0,0 +1,362 @@
+use crate::state::State;
+use crate::syllables::{self, has_diaeresis, is, is_double, Word};
+use crate::{Letter, Quantity};
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+use Letter::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Long,
+    Short,
+    // The last syllable of the line, which may be either.
+    Anceps,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaesuraKind {
+    // After the first syllable of the third foot
+    Penthemimeral,
+    // After the second syllable of a dactylic third foot
+    Trochaic,
+    // After the first syllable of the fourth foot
+    Hephthemimeral,
+    // Word end after a dactylic fourth foot
+    BucolicDiaeresis,
+}
+
+// A syllable as scanned, by the character range of its vowels. Two vowels joined by synizesis
+// make one syllable.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct MetricalSyllable {
+    pub start: usize,
+    pub end: usize,
+    pub mark: Mark,
+    // Index of the foot, from 0.
+    pub foot: usize,
+    // Index of the word in the line.
+    pub word: usize,
+}
+
+// A word end inside a foot, at a character offset of the line.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct Caesura {
+    pub position: usize,
+    pub kind: CaesuraKind,
+}
+
+#[wasm_bindgen]
+pub struct Scansion {
+    syllables: Vec<MetricalSyllable>,
+    caesurae: Vec<Caesura>,
+}
+
+#[wasm_bindgen]
+impl Scansion {
+    #[wasm_bindgen(getter)]
+    pub fn syllables(&self) -> Vec<MetricalSyllable> {
+        self.syllables.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn caesurae(&self) -> Vec<Caesura> {
+        self.caesurae.clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Weight {
+    Heavy,
+    Light,
+    // A vowel of unknown length, or a syllable that may be shortened or lengthened.
+    Either,
+}
+
+#[derive(Debug, Clone)]
+struct Unit {
+    range: Range<usize>,
+    weight: Weight,
+    word: usize,
+}
+
+// A vowel of the line, as a word index and a range of the word's letters.
+type Nucleus = (usize, Range<usize>);
+
+fn is_stop(state: State) -> bool {
+    [Pi, Beta, Phi, Tau, Delta, Theta, Kappa, Gamma, Chi]
+        .into_iter()
+        .any(|l| is(state, l))
+}
+
+fn is_liquid(state: State) -> bool {
+    [Lambda, Rho, Mu, Nu].into_iter().any(|l| is(state, l))
+}
+
+// The consonants between the vowel and the next one, across word ends, and whether they are
+// all in one word. An elided word has no vowel left, so its consonants count with the next.
+fn cluster(words: &[Word], nuclei: &[Nucleus], k: usize) -> (Vec<State>, bool) {
+    let (w, nucleus) = &nuclei[k];
+    let end = nuclei.get(k + 1);
+
+    let mut cluster = Vec::new();
+    let mut from_words = 0;
+    for (v, word) in words.iter().enumerate().skip(*w) {
+        let from = if v == *w { nucleus.end } else { 0 };
+        let to = match end {
+            Some((next, next_nucleus)) if *next == v => next_nucleus.start,
+            _ => word.letters.len(),
+        };
+        if to > from {
+            from_words += 1;
+        }
+        cluster.extend(&word.letters[from..to]);
+        if end.is_some_and(|(next, _)| *next == v) {
+            break;
+        }
+    }
+
+    (cluster, from_words <= 1)
+}
+
+fn weight(words: &[Word], nuclei: &[Nucleus], k: usize, nature: Quantity) -> Weight {
+    // The last syllable of the line is anceps.
+    let Some((next_word, _)) = nuclei.get(k + 1) else {
+        return Weight::Either;
+    };
+
+    let (cluster, in_one_word) = cluster(words, nuclei, k);
+    let consonants: usize = cluster
+        .iter()
+        .map(|&state| if is_double(state) { 2 } else { 1 })
+        .sum();
+
+    match (nature, consonants) {
+        // A stop before a liquid or nasal may leave the syllable short.
+        (Quantity::Short | Quantity::None, 2)
+            if cluster.len() == 2
+                && in_one_word
+                && is_stop(cluster[0])
+                && is_liquid(cluster[1]) =>
+        {
+            Weight::Either
+        }
+        (_, 2..) => Weight::Heavy,
+        // Epic correption: a long vowel at the end of a word may be shortened before a vowel.
+        (Quantity::Long, 0) if *next_word != nuclei[k].0 => Weight::Either,
+        (Quantity::Long, _) => Weight::Heavy,
+        (Quantity::Short, _) => Weight::Light,
+        (Quantity::None, _) => Weight::Either,
+    }
+}
+
+// The syllables of the line, and for each one that may merge with the next by synizesis, the
+// merged syllable.
+fn units(words: &[Word]) -> (Vec<Unit>, Vec<Option<Unit>>) {
+    let nuclei: Vec<Nucleus> = words
+        .iter()
+        .enumerate()
+        .flat_map(|(w, word)| {
+            syllables::nuclei(&word.letters)
+                .into_iter()
+                .map(move |nucleus| (w, nucleus))
+        })
+        .collect();
+    let range = |(w, nucleus): &Nucleus| {
+        let ranges = &words[*w].ranges;
+        ranges[nucleus.start].start..ranges[nucleus.end - 1].end
+    };
+
+    let mut units = Vec::new();
+    let mut merged = Vec::new();
+    for (k, (w, nucleus)) in nuclei.iter().enumerate() {
+        let nature = syllables::length(&words[*w].letters, nucleus);
+        units.push(Unit {
+            range: range(&nuclei[k]),
+            weight: weight(words, &nuclei, k, nature),
+            word: *w,
+        });
+
+        // An ε right before another vowel of the word, as in θεοί or Πηληϊάδεω.
+        let synizesis = nuclei.get(k + 1).filter(|(v, next)| {
+            v == w
+                && next.start == nucleus.end
+                && nucleus.len() == 1
+                && is(words[*w].letters[nucleus.start], Epsilon)
+                && !has_diaeresis(words[*w].letters[next.start])
+        });
+        merged.push(synizesis.map(|next| Unit {
+            range: range(&nuclei[k]).start..range(next).end,
+            weight: weight(words, &nuclei, k + 1, Quantity::Long),
+            word: *w,
+        }));
+    }
+
+    (units, merged)
+}
+
+fn fits(weight: Weight, mark: Mark) -> bool {
+    match mark {
+        Mark::Long => weight != Weight::Light,
+        Mark::Short => weight != Weight::Heavy,
+        Mark::Anceps => true,
+    }
+}
+
+struct Scanner<'a> {
+    units: &'a [Unit],
+    merged: &'a [Option<Unit>],
+    scanned: Vec<MetricalSyllable>,
+    found: Vec<Vec<MetricalSyllable>>,
+}
+
+impl Scanner<'_> {
+    // The syllables that can start at unit `i`, with the unit after each.
+    fn next(&self, i: usize) -> Vec<(Unit, usize)> {
+        let mut next = Vec::new();
+        if let Some(unit) = self.units.get(i) {
+            next.push((unit.clone(), i + 1));
+        }
+        if let Some(Some(unit)) = self.merged.get(i) {
+            next.push((unit.clone(), i + 2));
+        }
+        next
+    }
+
+    // Scans the marks of a foot from unit `i`, then the rest of the line.
+    fn foot(&mut self, i: usize, foot: usize, marks: &[Mark]) {
+        let Some((&mark, rest)) = marks.split_first() else {
+            return self.feet(i, foot + 1);
+        };
+
+        for (unit, after) in self.next(i) {
+            if !fits(unit.weight, mark) {
+                continue;
+            }
+            self.scanned.push(MetricalSyllable {
+                start: unit.range.start,
+                end: unit.range.end,
+                mark,
+                foot,
+                word: unit.word,
+            });
+            self.foot(after, foot, rest);
+            self.scanned.pop();
+        }
+    }
+
+    fn feet(&mut self, i: usize, foot: usize) {
+        match foot {
+            0..=4 => {
+                self.foot(i, foot, &[Mark::Long, Mark::Short, Mark::Short]);
+                self.foot(i, foot, &[Mark::Long, Mark::Long]);
+            }
+            5 => self.foot(i, foot, &[Mark::Long, Mark::Anceps]),
+            _ if i == self.units.len() => self.found.push(self.scanned.clone()),
+            _ => {}
+        }
+    }
+}
+
+fn caesurae(words: &[Word], syllables: &[MetricalSyllable]) -> Vec<Caesura> {
+    let mut caesurae = Vec::new();
+
+    for (j, pair) in syllables.windows(2).enumerate() {
+        if pair[0].word == pair[1].word {
+            continue;
+        }
+        let foot = pair[0].foot;
+        let place = syllables[..j].iter().filter(|s| s.foot == foot).count();
+        let dactyl = syllables.iter().filter(|s| s.foot == foot).count() == 3;
+
+        let kind = match (foot, place) {
+            (2, 0) => CaesuraKind::Penthemimeral,
+            (2, 1) if dactyl => CaesuraKind::Trochaic,
+            (3, 0) => CaesuraKind::Hephthemimeral,
+            (3, 2) => CaesuraKind::BucolicDiaeresis,
+            _ => continue,
+        };
+        let ranges = &words[pair[0].word].ranges;
+        caesurae.push(Caesura {
+            position: ranges[ranges.len() - 1].end,
+            kind,
+        });
+    }
+
+    caesurae
+}
+
+// Scans a line of Greek text as a dactylic hexameter, returning every scansion the syllable
+// weights allow; none if the line does not scan.
+pub fn scan(s: &str) -> Vec<Scansion> {
+    let words = syllables::words(s);
+    let (units, merged) = units(&words);
+
+    let mut scanner = Scanner {
+        units: &units,
+        merged: &merged,
+        scanned: Vec::new(),
+        found: Vec::new(),
+    };
+    scanner.feet(0, 0);
+
+    scanner
+        .found
+        .into_iter()
+        .map(|syllables| Scansion {
+            caesurae: caesurae(&words, &syllables),
+            syllables,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The marks of each foot, as in `-uu|--`.
+    fn pattern(scansion: &Scansion) -> String {
+        let mut pattern = String::new();
+        for (i, syllable) in scansion.syllables.iter().enumerate() {
+            if i > 0 && scansion.syllables[i - 1].foot != syllable.foot {
+                pattern.push('|');
+            }
+            pattern.push(match syllable.mark {
+                Mark::Long => '-',
+                Mark::Short => 'u',
+                Mark::Anceps => 'x',
+            });
+        }
+        pattern
+    }
+
+    #[test]
+    fn scans_with_synizesis() {
+        let scansions = scan("μῆνιν ἄειδε θεὰ Πηληϊάδεω Ἀχιλῆος");
+        let patterns: Vec<String> = scansions.iter().map(pattern).collect();
+        assert_eq!(patterns, ["-uu|-uu|--|-uu|-uu|-x"]);
+    }
+
+    #[test]
+    fn finds_caesurae() {
+        let scansions = scan("ἄνδρα μοι ἔννεπε μοῦσα πολύτροπον ὃς μάλα πολλὰ");
+        assert_eq!(scansions.len(), 1);
+        assert_eq!(pattern(&scansions[0]), "-uu|-uu|-uu|-uu|-uu|-x");
+        let kinds: Vec<CaesuraKind> = scansions[0].caesurae.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [CaesuraKind::Trochaic, CaesuraKind::BucolicDiaeresis]
+        );
+    }
+
+    #[test]
+    fn rejects_prose() {
+        assert!(scan("ὁ ἄνθρωπος").is_empty());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{Case, Convention};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Letter {
+    Alpha,
+    Beta,
+    Gamma,
+    Delta,
+    Epsilon,
+    Zeta,
+    Eta,
+    Theta,
+    Iota,
+    Kappa,
+    Lambda,
+    Mu,
+    Nu,
+    Xi,
+    Omicron,
+    Pi,
+    Rho,
+    Sigma,
+    Tau,
+    Ypsilon,
+    Phi,
+    Chi,
+    Psi,
+    Omega,
+    Digamma,
+    Heta,
+    Koppa,
+    NumeralKoppa,
+    Stigma,
+    Sampi,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Diacritic {
+    Smooth,
+    Rough,
+    // Marks crasis. It takes the place of the breathing and looks like a smooth one.
+    Koronis,
+    Grave,
+    Acute,
+    Circumflex,
+    Iota,
+    Diaeresis,
+    Macron,
+    Breve,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Punctuation {
+    AnoTeleia,
+    QuestionMark,
+    // Marks elision, as opposed to the koronis of crasis.
+    Apostrophe,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SigmaForm {
+    Medial,
+    Final,
+    Lunate,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Key {
+    Letter(Letter, Case),
+    Diacritic(Diacritic),
+    // Marks the next letter as a capital, taking the diacritics typed in between.
+    Capital,
+    // Starts a letter typed as a two-key sequence, see `Scheme::symbol`.
+    Symbol,
+    // After a letter, an elision apostrophe or the koronis of the next vowel.
+    Koronis,
+    Punctuation(Punctuation),
+    Other,
+}
+
+pub trait Scheme {
+    fn key(&self, c: u8) -> Key;
+
+    // The letter typed as the `Key::Symbol` key followed by `c`.
+    fn symbol(&self, c: u8) -> Option<(Letter, Case)> {
+        let letter = match c.to_ascii_lowercase() {
+            b'q' => Letter::Koppa,
+            b'k' => Letter::NumeralKoppa,
+            b's' => Letter::Stigma,
+            b'p' => Letter::Sampi,
+            b'h' => Letter::Heta,
+            _ => return None,
+        };
+
+        Some((letter, case_of(c)))
+    }
+
+    // The form forced by typing `c` right after a sigma, as in Beta Code.
+    fn sigma(&self, c: u8) -> Option<SigmaForm> {
+        match c {
+            b'1' => Some(SigmaForm::Medial),
+            b'2' => Some(SigmaForm::Final),
+            b'3' => Some(SigmaForm::Lunate),
+            _ => None,
+        }
+    }
+
+    // In papyrology mode, the key that puts an underdot on the preceding letter.
+    fn underdot(&self, c: u8) -> bool {
+        c == b'?'
+    }
+
+    // In papyrology mode, lacunae, editorial additions and deletions.
+    fn bracket(&self, c: u8) -> bool {
+        matches!(c, b'[' | b']' | b'<' | b'>' | b'{' | b'}')
+    }
+}
+
+// Letters shared by every built-in scheme.
+fn common_letter(c: u8) -> Option<Letter> {
+    use Letter::*;
+
+    match c {
+        b'a' => Some(Alpha),
+        b'b' => Some(Beta),
+        b'g' => Some(Gamma),
+        b'd' => Some(Delta),
+        b'e' => Some(Epsilon),
+        b'z' => Some(Zeta),
+        b'h' => Some(Eta),
+        b'q' => Some(Theta),
+        b'i' => Some(Iota),
+        b'k' => Some(Kappa),
+        b'l' => Some(Lambda),
+        b'm' => Some(Mu),
+        b'n' => Some(Nu),
+        b'o' => Some(Omicron),
+        b'p' => Some(Pi),
+        b'r' => Some(Rho),
+        b's' => Some(Sigma),
+        b't' => Some(Tau),
+        b'f' => Some(Phi),
+        b'w' => Some(Omega),
+        b'v' => Some(Digamma),
+        _ => None,
+    }
+}
+
+fn perseus_letter(c: u8) -> Option<Letter> {
+    match c {
+        b'c' => Some(Letter::Xi),
+        b'u' => Some(Letter::Ypsilon),
+        b'x' => Some(Letter::Chi),
+        b'y' => Some(Letter::Psi),
+        _ => common_letter(c),
+    }
+}
+
+fn common_diacritic(c: u8) -> Option<Diacritic> {
+    use Diacritic::*;
+
+    match c {
+        b')' => Some(Smooth),
+        b'(' => Some(Rough),
+        b'\\' => Some(Grave),
+        b'/' => Some(Acute),
+        b'=' => Some(Circumflex),
+        b'|' => Some(Iota),
+        b'_' => Some(Macron),
+        b'^' => Some(Breve),
+        _ => None,
+    }
+}
+
+fn common_punctuation(c: u8) -> Option<Punctuation> {
+    match c {
+        b':' => Some(Punctuation::AnoTeleia),
+        b';' => Some(Punctuation::QuestionMark),
+        _ => None,
+    }
+}
+
+// Keys shared by every built-in scheme, other than letters.
+fn common_key(c: u8) -> Key {
+    if let Some(diacritic) = common_diacritic(c) {
+        return Key::Diacritic(diacritic);
+    }
+
+    common_punctuation(c).map_or(Key::Other, Key::Punctuation)
+}
+
+fn case_of(c: u8) -> Case {
+    if c.is_ascii_uppercase() {
+        Case::Capital
+    } else {
+        Case::Small
+    }
+}
+
+pub struct Latin;
+
+impl Scheme for Latin {
+    fn key(&self, c: u8) -> Key {
+        let letter = match c.to_ascii_lowercase() {
+            b'x' => Some(Letter::Xi),
+            b'y' => Some(Letter::Ypsilon),
+            b'c' => Some(Letter::Chi),
+            b'j' => Some(Letter::Psi),
+            lower => common_letter(lower),
+        };
+        if let Some(letter) = letter {
+            return Key::Letter(letter, case_of(c));
+        }
+
+        match c {
+            b'"' => Key::Diacritic(Diacritic::Diaeresis),
+            b'#' => Key::Symbol,
+            b'\'' => Key::Koronis,
+            _ => common_key(c),
+        }
+    }
+}
+
+pub struct Perseus;
+
+impl Scheme for Perseus {
+    fn key(&self, c: u8) -> Key {
+        if let Some(letter) = perseus_letter(c.to_ascii_lowercase()) {
+            return Key::Letter(letter, case_of(c));
+        }
+
+        match c {
+            b'+' => Key::Diacritic(Diacritic::Diaeresis),
+            b'#' => Key::Symbol,
+            b'\'' => Key::Koronis,
+            _ => common_key(c),
+        }
+    }
+}
+
+pub struct BetaCode;
+
+impl Scheme for BetaCode {
+    fn key(&self, c: u8) -> Key {
+        // Letters are case-insensitive; capitals are marked by a preceding asterisk.
+        if let Some(letter) = perseus_letter(c.to_ascii_lowercase()) {
+            return Key::Letter(letter, Case::Small);
+        }
+
+        match c {
+            b'+' => Key::Diacritic(Diacritic::Diaeresis),
+            b'*' => Key::Capital,
+            b'#' => Key::Symbol,
+            // Beta Code writes a koronis as a smooth breathing.
+            b'\'' => Key::Punctuation(Punctuation::Apostrophe),
+            _ => common_key(c),
+        }
+    }
+
+    // TLG numbering, plus the mnemonic letters of the other schemes. Capitals take an asterisk.
+    fn symbol(&self, c: u8) -> Option<(Letter, Case)> {
+        let letter = match c.to_ascii_lowercase() {
+            b'1' | b'k' => Letter::NumeralKoppa,
+            b'2' | b's' => Letter::Stigma,
+            b'3' | b'q' => Letter::Koppa,
+            b'5' | b'p' => Letter::Sampi,
+            b'h' => Letter::Heta,
+            _ => return None,
+        };
+
+        Some((letter, Case::Small))
+    }
+}
+
+impl Scheme for Convention {
+    fn key(&self, c: u8) -> Key {
+        match self {
+            Convention::Latin => Latin.key(c),
+            Convention::Perseus => Perseus.key(c),
+            Convention::BetaCode => BetaCode.key(c),
+        }
+    }
+
+    fn symbol(&self, c: u8) -> Option<(Letter, Case)> {
+        match self {
+            Convention::Latin => Latin.symbol(c),
+            Convention::Perseus => Perseus.symbol(c),
+            Convention::BetaCode => BetaCode.symbol(c),
+        }
+    }
+}
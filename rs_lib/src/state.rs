@@ -1,10 +1,20 @@
+use crate::scheme::{Diacritic, Key, Letter, Punctuation, Scheme, SigmaForm};
+use crate::syllables::is_diphthong;
 use crate::unicode as uc;
-use crate::{Accent, Breathing, Case, Diaeresis, Subscript};
+use crate::{
+    Accent, AcuteForm, AnoTeleiaForm, ApostropheForm, Breathing, Case, Diaeresis, Form, IotaForm,
+    Options, Orthography, Quantity, Subscript,
+};
 
-#[derive(Debug, Clone, Copy)]
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
     Initial,
-    Alpha(Case, Breathing, Accent, Subscript),
+    Alpha(Case, Breathing, Accent, Subscript, Quantity),
     Beta(Case),
     Gamma(Case),
     Delta(Case),
@@ -12,7 +22,7 @@ pub enum State {
     Zeta(Case),
     Eta(Case, Breathing, Accent, Subscript),
     Theta(Case),
-    Iota(Case, Breathing, Accent, Diaeresis),
+    Iota(Case, Breathing, Accent, Diaeresis, Quantity),
     Kappa(Case),
     Lambda(Case),
     Mu(Case),
@@ -22,130 +32,270 @@ pub enum State {
     Pi(Case),
     Rho(Case, Breathing),
     Sigma(Case),
+    // Sigmas whose form was chosen explicitly rather than by position.
+    MedialSigma(Case),
+    FinalSigma(Case),
+    LunateSigma(Case),
     Tau(Case),
-    Ypsilon(Case, Breathing, Accent, Diaeresis),
+    Ypsilon(Case, Breathing, Accent, Diaeresis, Quantity),
     Phi(Case),
     Chi(Case),
     Psi(Case),
     Omega(Case, Breathing, Accent, Subscript),
+    Digamma(Case),
+    Heta(Case),
+    Koppa(Case),
+    NumeralKoppa(Case),
+    Stigma(Case),
+    Sampi(Case),
     Koronis,
+    // `Key::Koronis` after a letter: elision, unless a vowel follows and takes the koronis.
+    Apostrophe,
+    // `Key::Koronis` that can only be elision: after a sigma, as in σ’ for σε, or in monotonic
+    // text. A sigma before it stays medial.
+    Elision,
+    Punctuation(Punctuation),
+    Asterisk(Breathing, Accent, Subscript, Diaeresis),
+    // `Key::Symbol` awaiting the key that selects the letter.
+    Symbol(Case),
+    // `Key::Symbol` after a letter, i.e. the numeral sign, unless the next key selects a letter.
+    Keraia,
 }
 
-fn transit(state: State, c: u8) -> State {
+pub fn letter(letter: Letter, case: Case) -> State {
     use State::*;
 
-    match c {
-        b'A' => Alpha(
-            Case::Capital,
+    match letter {
+        Letter::Alpha => Alpha(
+            case,
             Breathing::None,
             Accent::None,
             Subscript::None,
+            Quantity::None,
         ),
-        b'B' => Beta(Case::Capital),
-        b'G' => Gamma(Case::Capital),
-        b'D' => Delta(Case::Capital),
-        b'E' => Epsilon(Case::Capital, Breathing::None, Accent::None),
-        b'Z' => Zeta(Case::Capital),
-        b'H' => Eta(
-            Case::Capital,
-            Breathing::None,
-            Accent::None,
-            Subscript::None,
-        ),
-        b'Q' => Theta(Case::Capital),
-        b'I' => Iota(
-            Case::Capital,
+        Letter::Beta => Beta(case),
+        Letter::Gamma => Gamma(case),
+        Letter::Delta => Delta(case),
+        Letter::Epsilon => Epsilon(case, Breathing::None, Accent::None),
+        Letter::Zeta => Zeta(case),
+        Letter::Eta => Eta(case, Breathing::None, Accent::None, Subscript::None),
+        Letter::Theta => Theta(case),
+        Letter::Iota => Iota(
+            case,
             Breathing::None,
             Accent::None,
             Diaeresis::None,
+            Quantity::None,
         ),
-        b'K' => Kappa(Case::Capital),
-        b'L' => Lambda(Case::Capital),
-        b'M' => Mu(Case::Capital),
-        b'N' => Nu(Case::Capital),
-        b'X' => Xi(Case::Capital),
-        b'O' => Omicron(Case::Capital, Breathing::None, Accent::None),
-        b'P' => Pi(Case::Capital),
-        b'R' => Rho(Case::Capital, Breathing::None),
-        b'S' => Sigma(Case::Capital),
-        b'T' => Tau(Case::Capital),
-        b'Y' => Ypsilon(
-            Case::Capital,
+        Letter::Kappa => Kappa(case),
+        Letter::Lambda => Lambda(case),
+        Letter::Mu => Mu(case),
+        Letter::Nu => Nu(case),
+        Letter::Xi => Xi(case),
+        Letter::Omicron => Omicron(case, Breathing::None, Accent::None),
+        Letter::Pi => Pi(case),
+        Letter::Rho => Rho(case, Breathing::None),
+        Letter::Sigma => Sigma(case),
+        Letter::Tau => Tau(case),
+        Letter::Ypsilon => Ypsilon(
+            case,
             Breathing::None,
             Accent::None,
             Diaeresis::None,
+            Quantity::None,
         ),
-        b'F' => Phi(Case::Capital),
-        b'C' => Chi(Case::Capital),
-        b'J' => Psi(Case::Capital),
-        b'W' => Omega(
-            Case::Capital,
+        Letter::Phi => Phi(case),
+        Letter::Chi => Chi(case),
+        Letter::Psi => Psi(case),
+        Letter::Omega => Omega(case, Breathing::None, Accent::None, Subscript::None),
+        Letter::Digamma => Digamma(case),
+        Letter::Heta => Heta(case),
+        Letter::Koppa => Koppa(case),
+        Letter::NumeralKoppa => NumeralKoppa(case),
+        Letter::Stigma => Stigma(case),
+        Letter::Sampi => Sampi(case),
+    }
+}
+
+fn transit(state: State, c: u8, scheme: &dyn Scheme, options: &Options) -> State {
+    use State::*;
+
+    if let Sigma(case) = state {
+        match scheme.sigma(c) {
+            Some(SigmaForm::Medial) => return MedialSigma(case),
+            Some(SigmaForm::Final) => return FinalSigma(case),
+            Some(SigmaForm::Lunate) => return LunateSigma(case),
+            None => {}
+        }
+    }
+
+    let symbol_case = match state {
+        Symbol(case) => Some(case),
+        Keraia => Some(Case::Small),
+        _ => None,
+    };
+    if let Some(symbol_case) = symbol_case {
+        return match scheme.symbol(c) {
+            Some((l, case)) => match symbol_case {
+                Case::Capital => letter(l, Case::Capital),
+                Case::Small => letter(l, case),
+            },
+            None => transit(Initial, c, scheme, options),
+        };
+    }
+
+    match (state, scheme.key(c)) {
+        // Diacritics written between the asterisk and the letter belong to the capital.
+        (Asterisk(b, a, s, d), Key::Letter(l, _)) => {
+            apply_prefix(letter(l, Case::Capital), b, a, s, d)
+        }
+        // Crasis: the koronis goes on the vowel after it.
+        (Apostrophe, Key::Letter(l, case)) if letter(l, case).is_vowel() => {
+            toggle(letter(l, case), Diacritic::Koronis)
+        }
+        // Like a breathing, the koronis moves on to the second vowel of a diphthong.
+        (_, Key::Letter(l, case)) if state.passes_koronis(letter(l, case)) => {
+            toggle(letter(l, case), Diacritic::Koronis)
+        }
+        (_, Key::Letter(l, case)) => letter(l, case),
+
+        (_, Key::Diacritic(d)) => match options.orthography {
+            Orthography::Polytonic => toggle(state, d),
+            // Breathings and iota subscripts are ignored, and every accent is a tonos.
+            Orthography::Monotonic => match d {
+                Diacritic::Smooth | Diacritic::Rough | Diacritic::Koronis | Diacritic::Iota => {
+                    state
+                }
+                Diacritic::Grave | Diacritic::Acute | Diacritic::Circumflex => {
+                    toggle(state, Diacritic::Acute)
+                }
+                Diacritic::Diaeresis | Diacritic::Macron | Diacritic::Breve => toggle(state, d),
+            },
+        },
+
+        (_, Key::Capital) => Asterisk(
             Breathing::None,
             Accent::None,
             Subscript::None,
+            Diaeresis::None,
         ),
 
-        b'a' => Alpha(Case::Small, Breathing::None, Accent::None, Subscript::None),
-        b'b' => Beta(Case::Small),
-        b'g' => Gamma(Case::Small),
-        b'd' => Delta(Case::Small),
-        b'e' => Epsilon(Case::Small, Breathing::None, Accent::None),
-        b'z' => Zeta(Case::Small),
-        b'h' => Eta(Case::Small, Breathing::None, Accent::None, Subscript::None),
-        b'q' => Theta(Case::Small),
-        b'i' => Iota(Case::Small, Breathing::None, Accent::None, Diaeresis::None),
-        b'k' => Kappa(Case::Small),
-        b'l' => Lambda(Case::Small),
-        b'm' => Mu(Case::Small),
-        b'n' => Nu(Case::Small),
-        b'x' => Xi(Case::Small),
-        b'o' => Omicron(Case::Small, Breathing::None, Accent::None),
-        b'p' => Pi(Case::Small),
-        b'r' => Rho(Case::Small, Breathing::None),
-        b's' => Sigma(Case::Small),
-        b't' => Tau(Case::Small),
-        b'y' => Ypsilon(Case::Small, Breathing::None, Accent::None, Diaeresis::None),
-        b'f' => Phi(Case::Small),
-        b'c' => Chi(Case::Small),
-        b'j' => Psi(Case::Small),
-        b'w' => Omega(Case::Small, Breathing::None, Accent::None, Subscript::None),
-
-        b')' => toggle_smooth(state),
-        b'(' => toggle_rough(state),
-        b'\\' => toggle_grave(state),
-        b'/' => toggle_acute(state),
-        b'=' => toggle_circumflex(state),
-        b'|' => toggle_iota(state),
-        b'"' => toggle_diaeresis(state),
-
-        b'\'' => Koronis,
-
-        _ => Initial,
+        (Asterisk(_, _, _, _), Key::Symbol) => Symbol(Case::Capital),
+        (_, Key::Symbol) if state.is_vowel() || state.is_consonant() => Keraia,
+        (_, Key::Symbol) => Symbol(Case::Small),
+
+        (_, Key::Koronis) if state.is_vowel() || state.is_consonant() => {
+            match options.orthography {
+                Orthography::Polytonic if state.bare() != Sigma(Case::Small) => Apostrophe,
+                _ => Elision,
+            }
+        }
+        (_, Key::Koronis) => Koronis,
+
+        (_, Key::Punctuation(p)) if options.punctuation => State::Punctuation(p),
+        (_, Key::Punctuation(_)) => Initial,
+
+        (_, Key::Other) => Initial,
+    }
+}
+
+fn apply_prefix(
+    state: State,
+    breathing: Breathing,
+    accent: Accent,
+    subscript: Subscript,
+    diaeresis: Diaeresis,
+) -> State {
+    diacritics(breathing, accent, subscript, diaeresis).fold(state, toggle)
+}
+
+// The quantity mark goes first, next to the letter.
+fn quantity(quantity: Quantity) -> Option<Diacritic> {
+    match quantity {
+        Quantity::None => None,
+        Quantity::Long => Some(Diacritic::Macron),
+        Quantity::Short => Some(Diacritic::Breve),
     }
 }
 
-fn is_diacritics(c: u8) -> bool {
-    matches!(c, b')' | b'(' | b'\\' | b'/' | b'=' | b'|' | b'"')
+// Diacritics in canonical order: breathing, diaeresis, accent, iota subscript.
+fn diacritics(
+    breathing: Breathing,
+    accent: Accent,
+    subscript: Subscript,
+    diaeresis: Diaeresis,
+) -> impl Iterator<Item = Diacritic> {
+    let breathing = match breathing {
+        Breathing::None => None,
+        Breathing::Smooth => Some(Diacritic::Smooth),
+        Breathing::Rough => Some(Diacritic::Rough),
+        Breathing::Koronis => Some(Diacritic::Koronis),
+    };
+    let diaeresis = match diaeresis {
+        Diaeresis::None => None,
+        Diaeresis::Some => Some(Diacritic::Diaeresis),
+    };
+    let accent = match accent {
+        Accent::None => None,
+        Accent::Grave => Some(Diacritic::Grave),
+        Accent::Acute => Some(Diacritic::Acute),
+        Accent::Circumflex => Some(Diacritic::Circumflex),
+    };
+    let subscript = match subscript {
+        Subscript::None => None,
+        Subscript::Iota => Some(Diacritic::Iota),
+    };
+
+    [breathing, diaeresis, accent, subscript]
+        .into_iter()
+        .flatten()
+}
+
+pub fn toggle(state: State, diacritic: Diacritic) -> State {
+    match diacritic {
+        Diacritic::Smooth => toggle_smooth(state),
+        Diacritic::Rough => toggle_rough(state),
+        Diacritic::Koronis => toggle_koronis(state),
+        Diacritic::Grave => toggle_grave(state),
+        Diacritic::Acute => toggle_acute(state),
+        Diacritic::Circumflex => toggle_circumflex(state),
+        Diacritic::Iota => toggle_iota(state),
+        Diacritic::Diaeresis => toggle_diaeresis(state),
+        Diacritic::Macron => toggle_macron(state),
+        Diacritic::Breve => toggle_breve(state),
+    }
+}
+
+fn is_diacritics(c: u8, scheme: &dyn Scheme) -> bool {
+    matches!(scheme.key(c), Key::Diacritic(_))
+}
+
+fn is_vowel(c: u8, scheme: &dyn Scheme) -> bool {
+    matches!(scheme.key(c), Key::Letter(l, case) if letter(l, case).is_vowel())
 }
 
 fn toggle_smooth(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, Breathing::Smooth, c, d) => Alpha(a, Breathing::None, c, d),
-        Alpha(a, _, c, d) => Alpha(a, Breathing::Smooth, c, d),
+        Alpha(a, Breathing::Smooth, c, d, e) => Alpha(a, Breathing::None, c, d, e),
+        Alpha(a, _, c, d, e) => Alpha(a, Breathing::Smooth, c, d, e),
         Epsilon(a, Breathing::Smooth, c) => Epsilon(a, Breathing::None, c),
         Epsilon(a, _, c) => Epsilon(a, Breathing::Smooth, c),
         Eta(a, Breathing::Smooth, c, d) => Eta(a, Breathing::None, c, d),
         Eta(a, _, c, d) => Eta(a, Breathing::Smooth, c, d),
-        Iota(a, Breathing::Smooth, c, d) => Iota(a, Breathing::None, c, d),
-        Iota(a, _, c, d) => Iota(a, Breathing::Smooth, c, d),
+        Iota(a, Breathing::Smooth, c, d, e) => Iota(a, Breathing::None, c, d, e),
+        Iota(a, _, c, d, e) => Iota(a, Breathing::Smooth, c, d, e),
         Omicron(a, Breathing::Smooth, c) => Omicron(a, Breathing::None, c),
         Omicron(a, _, c) => Omicron(a, Breathing::Smooth, c),
-        Ypsilon(a, Breathing::Smooth, c, d) => Ypsilon(a, Breathing::None, c, d),
-        Ypsilon(a, _, c, d) => Ypsilon(a, Breathing::Smooth, c, d),
+        Ypsilon(a, Breathing::Smooth, c, d, e) => Ypsilon(a, Breathing::None, c, d, e),
+        Ypsilon(a, _, c, d, e) => Ypsilon(a, Breathing::Smooth, c, d, e),
         Omega(a, Breathing::Smooth, c, d) => Omega(a, Breathing::None, c, d),
         Omega(a, _, c, d) => Omega(a, Breathing::Smooth, c, d),
+        Rho(a, Breathing::Smooth) => Rho(a, Breathing::None),
+        Rho(a, _) => Rho(a, Breathing::Smooth),
+        Asterisk(Breathing::Smooth, b, c, d) => Asterisk(Breathing::None, b, c, d),
+        Asterisk(_, b, c, d) => Asterisk(Breathing::Smooth, b, c, d),
         _ => state,
     }
 }
@@ -153,20 +303,45 @@ fn toggle_rough(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, Breathing::Rough, c, d) => Alpha(a, Breathing::None, c, d),
-        Alpha(a, _, c, d) => Alpha(a, Breathing::Rough, c, d),
+        Alpha(a, Breathing::Rough, c, d, e) => Alpha(a, Breathing::None, c, d, e),
+        Alpha(a, _, c, d, e) => Alpha(a, Breathing::Rough, c, d, e),
         Epsilon(a, Breathing::Rough, c) => Epsilon(a, Breathing::None, c),
         Epsilon(a, _, c) => Epsilon(a, Breathing::Rough, c),
         Eta(a, Breathing::Rough, c, d) => Eta(a, Breathing::None, c, d),
         Eta(a, _, c, d) => Eta(a, Breathing::Rough, c, d),
-        Iota(a, Breathing::Rough, c, d) => Iota(a, Breathing::None, c, d),
-        Iota(a, _, c, d) => Iota(a, Breathing::Rough, c, d),
+        Iota(a, Breathing::Rough, c, d, e) => Iota(a, Breathing::None, c, d, e),
+        Iota(a, _, c, d, e) => Iota(a, Breathing::Rough, c, d, e),
         Omicron(a, Breathing::Rough, c) => Omicron(a, Breathing::None, c),
         Omicron(a, _, c) => Omicron(a, Breathing::Rough, c),
-        Ypsilon(a, Breathing::Rough, c, d) => Ypsilon(a, Breathing::None, c, d),
-        Ypsilon(a, _, c, d) => Ypsilon(a, Breathing::Rough, c, d),
+        Ypsilon(a, Breathing::Rough, c, d, e) => Ypsilon(a, Breathing::None, c, d, e),
+        Ypsilon(a, _, c, d, e) => Ypsilon(a, Breathing::Rough, c, d, e),
         Omega(a, Breathing::Rough, c, d) => Omega(a, Breathing::None, c, d),
         Omega(a, _, c, d) => Omega(a, Breathing::Rough, c, d),
+        Rho(a, Breathing::Rough) => Rho(a, Breathing::None),
+        Rho(a, _) => Rho(a, Breathing::Rough),
+        Asterisk(Breathing::Rough, b, c, d) => Asterisk(Breathing::None, b, c, d),
+        Asterisk(_, b, c, d) => Asterisk(Breathing::Rough, b, c, d),
+        _ => state,
+    }
+}
+fn toggle_koronis(state: State) -> State {
+    use State::*;
+
+    match state {
+        Alpha(a, Breathing::Koronis, c, d, e) => Alpha(a, Breathing::None, c, d, e),
+        Alpha(a, _, c, d, e) => Alpha(a, Breathing::Koronis, c, d, e),
+        Epsilon(a, Breathing::Koronis, c) => Epsilon(a, Breathing::None, c),
+        Epsilon(a, _, c) => Epsilon(a, Breathing::Koronis, c),
+        Eta(a, Breathing::Koronis, c, d) => Eta(a, Breathing::None, c, d),
+        Eta(a, _, c, d) => Eta(a, Breathing::Koronis, c, d),
+        Iota(a, Breathing::Koronis, c, d, e) => Iota(a, Breathing::None, c, d, e),
+        Iota(a, _, c, d, e) => Iota(a, Breathing::Koronis, c, d, e),
+        Omicron(a, Breathing::Koronis, c) => Omicron(a, Breathing::None, c),
+        Omicron(a, _, c) => Omicron(a, Breathing::Koronis, c),
+        Ypsilon(a, Breathing::Koronis, c, d, e) => Ypsilon(a, Breathing::None, c, d, e),
+        Ypsilon(a, _, c, d, e) => Ypsilon(a, Breathing::Koronis, c, d, e),
+        Omega(a, Breathing::Koronis, c, d) => Omega(a, Breathing::None, c, d),
+        Omega(a, _, c, d) => Omega(a, Breathing::Koronis, c, d),
         _ => state,
     }
 }
@@ -175,20 +350,22 @@ fn toggle_grave(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, b, Accent::Grave, d) => Alpha(a, b, Accent::None, d),
-        Alpha(a, b, _, d) => Alpha(a, b, Accent::Grave, d),
+        Alpha(a, b, Accent::Grave, d, e) => Alpha(a, b, Accent::None, d, e),
+        Alpha(a, b, _, d, e) => Alpha(a, b, Accent::Grave, d, e),
         Epsilon(a, b, Accent::Grave) => Epsilon(a, b, Accent::None),
         Epsilon(a, b, _) => Epsilon(a, b, Accent::Grave),
         Eta(a, b, Accent::Grave, d) => Eta(a, b, Accent::None, d),
         Eta(a, b, _, d) => Eta(a, b, Accent::Grave, d),
-        Iota(a, b, Accent::Grave, d) => Iota(a, b, Accent::None, d),
-        Iota(a, b, _, d) => Iota(a, b, Accent::Grave, d),
+        Iota(a, b, Accent::Grave, d, e) => Iota(a, b, Accent::None, d, e),
+        Iota(a, b, _, d, e) => Iota(a, b, Accent::Grave, d, e),
         Omicron(a, b, Accent::Grave) => Omicron(a, b, Accent::None),
         Omicron(a, b, _) => Omicron(a, b, Accent::Grave),
-        Ypsilon(a, b, Accent::Grave, d) => Ypsilon(a, b, Accent::None, d),
-        Ypsilon(a, b, _, d) => Ypsilon(a, b, Accent::Grave, d),
+        Ypsilon(a, b, Accent::Grave, d, e) => Ypsilon(a, b, Accent::None, d, e),
+        Ypsilon(a, b, _, d, e) => Ypsilon(a, b, Accent::Grave, d, e),
         Omega(a, b, Accent::Grave, d) => Omega(a, b, Accent::None, d),
         Omega(a, b, _, d) => Omega(a, b, Accent::Grave, d),
+        Asterisk(a, Accent::Grave, c, d) => Asterisk(a, Accent::None, c, d),
+        Asterisk(a, _, c, d) => Asterisk(a, Accent::Grave, c, d),
         _ => state,
     }
 }
@@ -196,20 +373,22 @@ fn toggle_acute(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, b, Accent::Acute, d) => Alpha(a, b, Accent::None, d),
-        Alpha(a, b, _, d) => Alpha(a, b, Accent::Acute, d),
+        Alpha(a, b, Accent::Acute, d, e) => Alpha(a, b, Accent::None, d, e),
+        Alpha(a, b, _, d, e) => Alpha(a, b, Accent::Acute, d, e),
         Epsilon(a, b, Accent::Acute) => Epsilon(a, b, Accent::None),
         Epsilon(a, b, _) => Epsilon(a, b, Accent::Acute),
         Eta(a, b, Accent::Acute, d) => Eta(a, b, Accent::None, d),
         Eta(a, b, _, d) => Eta(a, b, Accent::Acute, d),
-        Iota(a, b, Accent::Acute, d) => Iota(a, b, Accent::None, d),
-        Iota(a, b, _, d) => Iota(a, b, Accent::Acute, d),
+        Iota(a, b, Accent::Acute, d, e) => Iota(a, b, Accent::None, d, e),
+        Iota(a, b, _, d, e) => Iota(a, b, Accent::Acute, d, e),
         Omicron(a, b, Accent::Acute) => Omicron(a, b, Accent::None),
         Omicron(a, b, _) => Omicron(a, b, Accent::Acute),
-        Ypsilon(a, b, Accent::Acute, d) => Ypsilon(a, b, Accent::None, d),
-        Ypsilon(a, b, _, d) => Ypsilon(a, b, Accent::Acute, d),
+        Ypsilon(a, b, Accent::Acute, d, e) => Ypsilon(a, b, Accent::None, d, e),
+        Ypsilon(a, b, _, d, e) => Ypsilon(a, b, Accent::Acute, d, e),
         Omega(a, b, Accent::Acute, d) => Omega(a, b, Accent::None, d),
         Omega(a, b, _, d) => Omega(a, b, Accent::Acute, d),
+        Asterisk(a, Accent::Acute, c, d) => Asterisk(a, Accent::None, c, d),
+        Asterisk(a, _, c, d) => Asterisk(a, Accent::Acute, c, d),
         _ => state,
     }
 }
@@ -217,16 +396,18 @@ fn toggle_circumflex(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, b, Accent::Circumflex, d) => Alpha(a, b, Accent::None, d),
-        Alpha(a, b, _, d) => Alpha(a, b, Accent::Circumflex, d),
+        Alpha(a, b, Accent::Circumflex, d, e) => Alpha(a, b, Accent::None, d, e),
+        Alpha(a, b, _, d, e) => Alpha(a, b, Accent::Circumflex, d, e),
         Eta(a, b, Accent::Circumflex, d) => Eta(a, b, Accent::None, d),
         Eta(a, b, _, d) => Eta(a, b, Accent::Circumflex, d),
-        Iota(a, b, Accent::Circumflex, d) => Iota(a, b, Accent::None, d),
-        Iota(a, b, _, d) => Iota(a, b, Accent::Circumflex, d),
-        Ypsilon(a, b, Accent::Circumflex, d) => Ypsilon(a, b, Accent::None, d),
-        Ypsilon(a, b, _, d) => Ypsilon(a, b, Accent::Circumflex, d),
+        Iota(a, b, Accent::Circumflex, d, e) => Iota(a, b, Accent::None, d, e),
+        Iota(a, b, _, d, e) => Iota(a, b, Accent::Circumflex, d, e),
+        Ypsilon(a, b, Accent::Circumflex, d, e) => Ypsilon(a, b, Accent::None, d, e),
+        Ypsilon(a, b, _, d, e) => Ypsilon(a, b, Accent::Circumflex, d, e),
         Omega(a, b, Accent::Circumflex, d) => Omega(a, b, Accent::None, d),
         Omega(a, b, _, d) => Omega(a, b, Accent::Circumflex, d),
+        Asterisk(a, Accent::Circumflex, c, d) => Asterisk(a, Accent::None, c, d),
+        Asterisk(a, _, c, d) => Asterisk(a, Accent::Circumflex, c, d),
         _ => state,
     }
 }
@@ -235,12 +416,14 @@ fn toggle_iota(state: State) -> State {
     use State::*;
 
     match state {
-        Alpha(a, b, c, Subscript::Iota) => Alpha(a, b, c, Subscript::None),
-        Alpha(a, b, c, Subscript::None) => Alpha(a, b, c, Subscript::Iota),
+        Alpha(a, b, c, Subscript::Iota, e) => Alpha(a, b, c, Subscript::None, e),
+        Alpha(a, b, c, Subscript::None, e) => Alpha(a, b, c, Subscript::Iota, e),
         Eta(a, b, c, Subscript::Iota) => Eta(a, b, c, Subscript::None),
         Eta(a, b, c, Subscript::None) => Eta(a, b, c, Subscript::Iota),
         Omega(a, b, c, Subscript::Iota) => Omega(a, b, c, Subscript::None),
         Omega(a, b, c, Subscript::None) => Omega(a, b, c, Subscript::Iota),
+        Asterisk(a, b, Subscript::Iota, d) => Asterisk(a, b, Subscript::None, d),
+        Asterisk(a, b, Subscript::None, d) => Asterisk(a, b, Subscript::Iota, d),
         _ => state,
     }
 }
@@ -248,26 +431,232 @@ fn toggle_diaeresis(state: State) -> State {
     use State::*;
 
     match state {
-        Iota(a, b, c, Diaeresis::Some) => Iota(a, b, c, Diaeresis::None),
-        Iota(a, b, c, Diaeresis::None) => Iota(a, b, c, Diaeresis::Some),
-        Ypsilon(a, b, c, Diaeresis::Some) => Ypsilon(a, b, c, Diaeresis::None),
-        Ypsilon(a, b, c, Diaeresis::None) => Ypsilon(a, b, c, Diaeresis::Some),
+        Iota(a, b, c, Diaeresis::Some, e) => Iota(a, b, c, Diaeresis::None, e),
+        Iota(a, b, c, Diaeresis::None, e) => Iota(a, b, c, Diaeresis::Some, e),
+        Ypsilon(a, b, c, Diaeresis::Some, e) => Ypsilon(a, b, c, Diaeresis::None, e),
+        Ypsilon(a, b, c, Diaeresis::None, e) => Ypsilon(a, b, c, Diaeresis::Some, e),
+        Asterisk(a, b, c, Diaeresis::Some) => Asterisk(a, b, c, Diaeresis::None),
+        Asterisk(a, b, c, Diaeresis::None) => Asterisk(a, b, c, Diaeresis::Some),
         _ => state,
     }
 }
 
+fn toggle_macron(state: State) -> State {
+    use State::*;
+
+    match state {
+        Alpha(a, b, c, d, Quantity::Long) => Alpha(a, b, c, d, Quantity::None),
+        Alpha(a, b, c, d, _) => Alpha(a, b, c, d, Quantity::Long),
+        Iota(a, b, c, d, Quantity::Long) => Iota(a, b, c, d, Quantity::None),
+        Iota(a, b, c, d, _) => Iota(a, b, c, d, Quantity::Long),
+        Ypsilon(a, b, c, d, Quantity::Long) => Ypsilon(a, b, c, d, Quantity::None),
+        Ypsilon(a, b, c, d, _) => Ypsilon(a, b, c, d, Quantity::Long),
+        _ => state,
+    }
+}
+fn toggle_breve(state: State) -> State {
+    use State::*;
+
+    match state {
+        Alpha(a, b, c, d, Quantity::Short) => Alpha(a, b, c, d, Quantity::None),
+        Alpha(a, b, c, d, _) => Alpha(a, b, c, d, Quantity::Short),
+        Iota(a, b, c, d, Quantity::Short) => Iota(a, b, c, d, Quantity::None),
+        Iota(a, b, c, d, _) => Iota(a, b, c, d, Quantity::Short),
+        Ypsilon(a, b, c, d, Quantity::Short) => Ypsilon(a, b, c, d, Quantity::None),
+        Ypsilon(a, b, c, d, _) => Ypsilon(a, b, c, d, Quantity::Short),
+        _ => state,
+    }
+}
+
+fn punctuation(punctuation: Punctuation, options: &Options) -> &'static str {
+    let normalized = options.form != Form::Precomposed;
+
+    match punctuation {
+        Punctuation::AnoTeleia if normalized => uc::MIDDLE_DOT,
+        Punctuation::AnoTeleia => match options.ano_teleia {
+            AnoTeleiaForm::AnoTeleia => uc::ANO_TELEIA,
+            AnoTeleiaForm::MiddleDot => uc::MIDDLE_DOT,
+        },
+        Punctuation::QuestionMark if normalized => uc::SEMICOLON,
+        Punctuation::QuestionMark => uc::QUESTION_MARK,
+        Punctuation::Apostrophe => match options.apostrophe {
+            ApostropheForm::Quotation => uc::APOSTROPHE,
+            ApostropheForm::ModifierLetter => uc::MODIFIER_APOSTROPHE,
+        },
+    }
+}
+
 fn replace(buffer: &mut String, old: &str, new: &str) {
     let range = (buffer.len() - old.len())..(buffer.len());
     buffer.replace_range(range, new);
 }
 
+// Every precomposed glyph the tables can produce, by its code point.
+fn precomposed() -> HashMap<char, State> {
+    use Letter::*;
+
+    const LETTERS: [Letter; 30] = [
+        Alpha,
+        Beta,
+        Gamma,
+        Delta,
+        Epsilon,
+        Zeta,
+        Eta,
+        Theta,
+        Iota,
+        Kappa,
+        Lambda,
+        Mu,
+        Nu,
+        Xi,
+        Omicron,
+        Pi,
+        Rho,
+        Sigma,
+        Tau,
+        Ypsilon,
+        Phi,
+        Chi,
+        Psi,
+        Omega,
+        Digamma,
+        Heta,
+        Koppa,
+        NumeralKoppa,
+        Stigma,
+        Sampi,
+    ];
+    const BREATHINGS: [Option<Diacritic>; 3] =
+        [None, Some(Diacritic::Smooth), Some(Diacritic::Rough)];
+    const ACCENTS: [Option<Diacritic>; 4] = [
+        None,
+        Some(Diacritic::Grave),
+        Some(Diacritic::Acute),
+        Some(Diacritic::Circumflex),
+    ];
+    const EXTRAS: [Option<Diacritic>; 5] = [
+        None,
+        Some(Diacritic::Iota),
+        Some(Diacritic::Diaeresis),
+        Some(Diacritic::Macron),
+        Some(Diacritic::Breve),
+    ];
+
+    let mut glyphs = HashMap::new();
+    for case in [Case::Small, Case::Capital] {
+        for l in LETTERS {
+            for breathing in BREATHINGS {
+                for accent in ACCENTS {
+                    for extra in EXTRAS {
+                        let state = [breathing, accent, extra]
+                            .into_iter()
+                            .flatten()
+                            .fold(letter(l, case), toggle);
+                        if !state.is_precomposed() {
+                            continue;
+                        }
+                        let Some(c) = state.to_str().chars().next() else {
+                            continue;
+                        };
+                        glyphs.entry(c).or_insert(state);
+                        glyphs.entry(uc::oxia(c)).or_insert(state);
+                    }
+                }
+            }
+        }
+        for state in [State::FinalSigma(case), State::LunateSigma(case)] {
+            if let Some(c) = state.to_str().chars().next() {
+                glyphs.entry(c).or_insert(state);
+            }
+        }
+    }
+
+    glyphs
+}
+
+fn combining_diacritic(c: char) -> Option<Diacritic> {
+    match c {
+        '\u{0313}' | '\u{0343}' => Some(Diacritic::Smooth),
+        '\u{0314}' => Some(Diacritic::Rough),
+        '\u{0300}' => Some(Diacritic::Grave),
+        '\u{0301}' => Some(Diacritic::Acute),
+        '\u{0342}' => Some(Diacritic::Circumflex),
+        '\u{0345}' => Some(Diacritic::Iota),
+        '\u{0308}' => Some(Diacritic::Diaeresis),
+        '\u{0304}' => Some(Diacritic::Macron),
+        '\u{0306}' => Some(Diacritic::Breve),
+        _ => None,
+    }
+}
+
+fn read_punctuation(c: char) -> Option<State> {
+    let p = match c {
+        '\u{0387}' | '\u{00b7}' => Punctuation::AnoTeleia,
+        '\u{037e}' | ';' => Punctuation::QuestionMark,
+        '\u{2019}' | '\u{02bc}' | '\u{1fbd}' | '\'' => return Some(State::Apostrophe),
+        _ => return None,
+    };
+
+    Some(State::Punctuation(p))
+}
+
+// Reads Greek text, in any of the forms, back into glyphs along with their character ranges.
+// Letters take the combining marks after them; other characters are read as `Initial`.
+pub fn read(s: &str) -> Vec<(State, Range<usize>)> {
+    static PRECOMPOSED: OnceLock<HashMap<char, State>> = OnceLock::new();
+    let precomposed = PRECOMPOSED.get_or_init(precomposed);
+
+    let mut glyphs: Vec<(State, Range<usize>)> = Vec::new();
+    for (i, c) in s.chars().enumerate() {
+        if let Some((state, range)) = glyphs.last_mut() {
+            let is_letter = state.is_vowel() || state.is_consonant();
+            if is_letter && ('\u{0300}'..='\u{036f}').contains(&c) {
+                if let Some(diacritic) = combining_diacritic(c) {
+                    *state = toggle(*state, diacritic);
+                }
+                range.end = i + 1;
+                continue;
+            }
+        }
+
+        let state = precomposed
+            .get(&c)
+            .copied()
+            .or_else(|| read_punctuation(c))
+            .unwrap_or(State::Initial);
+        glyphs.push((state, i..i + 1));
+    }
+
+    glyphs
+}
+
+// Replaces the given character ranges of `s`, which must be in order, e.g. glyphs from `read`.
+pub fn rewrite(s: &str, rewrites: Vec<(Range<usize>, String)>) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut rewritten = String::new();
+    let mut copied = 0;
+    for (range, glyph) in rewrites {
+        rewritten.extend(&chars[copied..range.start]);
+        rewritten.push_str(&glyph);
+        copied = range.end;
+    }
+    rewritten.extend(&chars[copied..]);
+
+    rewritten
+}
+
 impl State {
-    fn to_str(self) -> &'static str {
+    pub fn to_str(self) -> &'static str {
         use State::*;
 
         match self {
             Initial => "",
-            Alpha(a, b, c, d) => uc::Alpha[(a, b, c, d)],
+            Alpha(a, b, c, d, Quantity::None) => uc::Alpha[(a, b, c, d)],
+            Alpha(a, Breathing::None, Accent::None, Subscript::None, e) => uc::Alpha[(a, e)],
+            // No code point carries a quantity together with other marks; `render` spells
+            // these out with combining marks.
+            Alpha(a, b, c, d, _) => uc::Alpha[(a, b, c, d)],
             Beta(a) => uc::Beta[a],
             Gamma(a) => uc::Gamma[a],
             Delta(a) => uc::Delta[a],
@@ -275,7 +664,9 @@ impl State {
             Zeta(a) => uc::Zeta[a],
             Eta(a, b, c, d) => uc::Eta[(a, b, c, d)],
             Theta(a) => uc::Theta[a],
-            Iota(a, b, c, d) => uc::Iota[(a, b, c, d)],
+            Iota(a, b, c, d, Quantity::None) => uc::Iota[(a, b, c, d)],
+            Iota(a, Breathing::None, Accent::None, Diaeresis::None, e) => uc::Iota[(a, e)],
+            Iota(a, b, c, d, _) => uc::Iota[(a, b, c, d)],
             Kappa(a) => uc::Kappa[a],
             Lambda(a) => uc::Lambda[a],
             Mu(a) => uc::Mu[a],
@@ -285,16 +676,270 @@ impl State {
             Pi(a) => uc::Pi[a],
             Rho(a, b) => uc::Rho[(a, b)],
             Sigma(a) => uc::Sigma[a],
+            MedialSigma(a) => uc::Sigma[a],
+            FinalSigma(a) => uc::FinalSigma[a],
+            LunateSigma(a) => uc::LunateSigma[a],
             Tau(a) => uc::Tau[a],
-            Ypsilon(a, b, c, d) => uc::Ypsilon[(a, b, c, d)],
+            Ypsilon(a, b, c, d, Quantity::None) => uc::Ypsilon[(a, b, c, d)],
+            Ypsilon(a, Breathing::None, Accent::None, Diaeresis::None, e) => uc::Ypsilon[(a, e)],
+            Ypsilon(a, b, c, d, _) => uc::Ypsilon[(a, b, c, d)],
             Phi(a) => uc::Phi[a],
             Chi(a) => uc::Chi[a],
             Psi(a) => uc::Psi[a],
             Omega(a, b, c, d) => uc::Omega[(a, b, c, d)],
+            Digamma(a) => uc::Digamma[a],
+            Heta(a) => uc::Heta[a],
+            Koppa(a) => uc::Koppa[a],
+            NumeralKoppa(a) => uc::NumeralKoppa[a],
+            Stigma(a) => uc::Stigma[a],
+            Sampi(a) => uc::Sampi[a],
             Koronis => uc::KORONIS,
+            Apostrophe | Elision => uc::APOSTROPHE,
+            Punctuation(p) => punctuation(p, &Options::default()),
+            Asterisk(_, _, _, _) => "",
+            Symbol(_) => uc::SYMBOL,
+            Keraia => uc::KERAIA,
         }
     }
 
+    // The letter without any diacritics.
+    fn base(self) -> Self {
+        use State::*;
+
+        match self {
+            Alpha(a, _, _, _, _) => Alpha(
+                a,
+                Breathing::None,
+                Accent::None,
+                Subscript::None,
+                Quantity::None,
+            ),
+            Epsilon(a, _, _) => Epsilon(a, Breathing::None, Accent::None),
+            Eta(a, _, _, _) => Eta(a, Breathing::None, Accent::None, Subscript::None),
+            Iota(a, _, _, _, _) => Iota(
+                a,
+                Breathing::None,
+                Accent::None,
+                Diaeresis::None,
+                Quantity::None,
+            ),
+            Omicron(a, _, _) => Omicron(a, Breathing::None, Accent::None),
+            Ypsilon(a, _, _, _, _) => Ypsilon(
+                a,
+                Breathing::None,
+                Accent::None,
+                Diaeresis::None,
+                Quantity::None,
+            ),
+            Omega(a, _, _, _) => Omega(a, Breathing::None, Accent::None, Subscript::None),
+            Rho(a, _) => Rho(a, Breathing::None),
+            _ => self,
+        }
+    }
+
+    fn marks(self) -> impl Iterator<Item = Diacritic> {
+        use State::*;
+
+        let (q, b, a, s, d) = match self {
+            Alpha(_, b, a, s, q) => (q, b, a, s, Diaeresis::None),
+            Eta(_, b, a, s) | Omega(_, b, a, s) => (Quantity::None, b, a, s, Diaeresis::None),
+            Iota(_, b, a, d, q) | Ypsilon(_, b, a, d, q) => (q, b, a, Subscript::None, d),
+            Epsilon(_, b, a) | Omicron(_, b, a) => {
+                (Quantity::None, b, a, Subscript::None, Diaeresis::None)
+            }
+            Rho(_, b) => (
+                Quantity::None,
+                b,
+                Accent::None,
+                Subscript::None,
+                Diaeresis::None,
+            ),
+            _ => (
+                Quantity::None,
+                Breathing::None,
+                Accent::None,
+                Subscript::None,
+                Diaeresis::None,
+            ),
+        };
+
+        quantity(q).into_iter().chain(diacritics(b, a, s, d))
+    }
+
+    pub fn render(self, options: &Options) -> Cow<'static, str> {
+        let glyph = self.cased(options);
+
+        match glyph.adscript(options) {
+            Some(vowel) => {
+                let case = if options.all_caps {
+                    Case::Capital
+                } else {
+                    Case::Small
+                };
+                let iota = letter(Letter::Iota, case);
+
+                let mut s = vowel.render_glyph(options).into_owned();
+                s.push_str(&iota.render_glyph(options));
+                Cow::Owned(s)
+            }
+            None => glyph.render_glyph(options),
+        }
+    }
+
+    // Whether the glyph renders with precomposed code points only.
+    pub fn renders_precomposed(self, options: &Options) -> bool {
+        let glyph = self.cased(options);
+        glyph.adscript(options).unwrap_or(glyph).is_precomposed()
+    }
+
+    fn cased(self, options: &Options) -> Self {
+        if options.all_caps {
+            self.with_case(Case::Capital)
+        } else {
+            self
+        }
+    }
+
+    // The vowel without its iota subscript, if the iota is to be written after it instead.
+    fn adscript(self, options: &Options) -> Option<Self> {
+        use State::*;
+
+        let (case, vowel) = match self {
+            Alpha(a, b, c, Subscript::Iota, e) => (a, Alpha(a, b, c, Subscript::None, e)),
+            Eta(a, b, c, Subscript::Iota) => (a, Eta(a, b, c, Subscript::None)),
+            Omega(a, b, c, Subscript::Iota) => (a, Omega(a, b, c, Subscript::None)),
+            _ => return None,
+        };
+        // All capitals spell the iota out, as in ΑΙ.
+        let form = match case {
+            _ if options.all_caps => IotaForm::Adscript,
+            Case::Capital => options.capital_iota,
+            Case::Small => options.small_iota,
+        };
+
+        (form == IotaForm::Adscript).then_some(vowel)
+    }
+
+    fn render_glyph(self, options: &Options) -> Cow<'static, str> {
+        if let Self::Sigma(a) = self {
+            if options.lunate_sigma {
+                return Cow::Borrowed(uc::LunateSigma[a]);
+            }
+        }
+        if let Self::Punctuation(p) = self {
+            return Cow::Borrowed(punctuation(p, options));
+        }
+        // Monotonic text has no koronis, only the apostrophe.
+        let monotonic = options.orthography == Orthography::Monotonic;
+        match self {
+            Self::Apostrophe | Self::Elision if options.punctuation || monotonic => {
+                return Cow::Borrowed(punctuation(Punctuation::Apostrophe, options));
+            }
+            Self::Apostrophe | Self::Elision => return Cow::Borrowed(uc::KORONIS),
+            Self::Koronis if monotonic => {
+                return Cow::Borrowed(punctuation(Punctuation::Apostrophe, options));
+            }
+            _ => {}
+        }
+        // U+0374 normalizes to the modifier letter prime.
+        if let (Self::Keraia, Form::Nfc | Form::Nfd) = (self, options.form) {
+            return Cow::Borrowed(uc::PRIME);
+        }
+
+        match options.form {
+            // Only the bare letter has a precomposed form with a quantity mark.
+            Form::Precomposed if self.has_quantity() => Cow::Owned(self.compose()),
+            Form::Precomposed if !self.is_precomposed() => Cow::Owned(self.decompose()),
+            // Monotonic text always takes the tonos.
+            Form::Precomposed => match (options.acute, options.orthography) {
+                (AcuteForm::Oxia, Orthography::Polytonic) => {
+                    Cow::Owned(self.to_str().chars().map(uc::oxia).collect())
+                }
+                _ => Cow::Borrowed(self.to_str()),
+            },
+            Form::Nfd => Cow::Owned(self.decompose()),
+            Form::Nfc => Cow::Owned(self.compose()),
+        }
+    }
+
+    // The small letter without any diacritics, with every sigma form as the plain sigma, for
+    // telling letters apart.
+    pub fn bare(self) -> Self {
+        match self.base().with_case(Case::Small) {
+            Self::MedialSigma(a) | Self::FinalSigma(a) | Self::LunateSigma(a) => Self::Sigma(a),
+            bare => bare,
+        }
+    }
+
+    // The same glyph in the given case.
+    fn with_case(self, case: Case) -> Self {
+        use State::*;
+
+        match self {
+            Alpha(_, b, c, d, e) => Alpha(case, b, c, d, e),
+            Beta(_) => Beta(case),
+            Gamma(_) => Gamma(case),
+            Delta(_) => Delta(case),
+            Epsilon(_, b, c) => Epsilon(case, b, c),
+            Zeta(_) => Zeta(case),
+            Eta(_, b, c, d) => Eta(case, b, c, d),
+            Theta(_) => Theta(case),
+            Iota(_, b, c, d, e) => Iota(case, b, c, d, e),
+            Kappa(_) => Kappa(case),
+            Lambda(_) => Lambda(case),
+            Mu(_) => Mu(case),
+            Nu(_) => Nu(case),
+            Xi(_) => Xi(case),
+            Omicron(_, b, c) => Omicron(case, b, c),
+            Pi(_) => Pi(case),
+            Rho(_, b) => Rho(case, b),
+            Sigma(_) => Sigma(case),
+            MedialSigma(_) => MedialSigma(case),
+            FinalSigma(_) => FinalSigma(case),
+            LunateSigma(_) => LunateSigma(case),
+            Tau(_) => Tau(case),
+            Ypsilon(_, b, c, d, e) => Ypsilon(case, b, c, d, e),
+            Phi(_) => Phi(case),
+            Chi(_) => Chi(case),
+            Psi(_) => Psi(case),
+            Omega(_, b, c, d) => Omega(case, b, c, d),
+            Digamma(_) => Digamma(case),
+            Heta(_) => Heta(case),
+            Koppa(_) => Koppa(case),
+            NumeralKoppa(_) => NumeralKoppa(case),
+            Stigma(_) => Stigma(case),
+            Sampi(_) => Sampi(case),
+            _ => self,
+        }
+    }
+
+    fn decompose(self) -> String {
+        let mut s = self.base().to_str().to_string();
+        s.extend(self.marks().map(uc::combining));
+        s
+    }
+
+    // Marks compose with the letter one by one as long as a precomposed form exists.
+    // A mark left uncomposed blocks later marks of the same combining class, i.e.
+    // anything but the iota subscript.
+    fn compose(self) -> String {
+        let mut composed = self.base();
+        let mut rest = String::new();
+        for mark in self.marks() {
+            let blocked = !rest.is_empty() && !matches!(mark, Diacritic::Iota);
+            let next = toggle(composed, mark);
+            if !blocked && next.is_precomposed() {
+                composed = next;
+            } else {
+                rest.push(uc::combining(mark));
+            }
+        }
+
+        let mut s = composed.to_str().to_string();
+        s.push_str(&rest);
+        s
+    }
+
     #[inline]
     pub fn initial() -> Self {
         Self::Initial
@@ -305,43 +950,208 @@ impl State {
         matches!(self, Self::Initial)
     }
 
-    pub fn rewrite_buffer(self, c: u8, buffer: &mut String) -> Self {
+    // Whether a sigma before this glyph is final.
+    pub fn ends_word(self) -> bool {
+        matches!(
+            self,
+            Self::Initial | Self::Apostrophe | Self::Punctuation(_)
+        )
+    }
+
+    #[inline]
+    pub fn is_pending_capital(self) -> bool {
+        matches!(self, Self::Asterisk(_, _, _, _))
+    }
+
+    // A sigma whose form depends on the next character.
+    #[inline]
+    pub fn is_pending_sigma(self) -> bool {
+        matches!(self, Self::Sigma(_))
+    }
+
+    pub fn is_vowel(self) -> bool {
+        use State::*;
+
+        matches!(
+            self,
+            Alpha(_, _, _, _, _)
+                | Epsilon(_, _, _)
+                | Eta(_, _, _, _)
+                | Iota(_, _, _, _, _)
+                | Omicron(_, _, _)
+                | Ypsilon(_, _, _, _, _)
+                | Omega(_, _, _, _)
+        )
+    }
+
+    pub fn is_consonant(self) -> bool {
+        !self.is_vowel()
+            && !matches!(
+                self,
+                Self::Initial
+                    | Self::Koronis
+                    | Self::Apostrophe
+                    | Self::Elision
+                    | Self::Punctuation(_)
+                    | Self::Asterisk(_, _, _, _)
+                    | Self::Symbol(_)
+                    | Self::Keraia
+            )
+    }
+
+    pub fn accent(self) -> Accent {
+        use State::*;
+
+        match self {
+            Alpha(_, _, a, _, _)
+            | Epsilon(_, _, a)
+            | Eta(_, _, a, _)
+            | Iota(_, _, a, _, _)
+            | Omicron(_, _, a)
+            | Ypsilon(_, _, a, _, _)
+            | Omega(_, _, a, _) => a,
+            _ => Accent::None,
+        }
+    }
+
+    pub fn breathing(self) -> Breathing {
+        use State::*;
+
+        match self {
+            Alpha(_, b, _, _, _)
+            | Epsilon(_, b, _)
+            | Eta(_, b, _, _)
+            | Iota(_, b, _, _, _)
+            | Omicron(_, b, _)
+            | Ypsilon(_, b, _, _, _)
+            | Omega(_, b, _, _)
+            | Rho(_, b) => b,
+            _ => Breathing::None,
+        }
+    }
+
+    // Whether the koronis on this vowel belongs on `next` instead, the second vowel of a
+    // diphthong.
+    pub fn passes_koronis(self, next: Self) -> bool {
+        use State::*;
+
+        let koronis = matches!(
+            self,
+            Alpha(_, Breathing::Koronis, _, _, _)
+                | Epsilon(_, Breathing::Koronis, _)
+                | Eta(_, Breathing::Koronis, _, _)
+                | Omicron(_, Breathing::Koronis, _)
+        );
+        koronis && next.is_vowel() && is_diphthong(self, next)
+    }
+
+    // The vowel once its koronis has moved on, see `passes_koronis`.
+    pub fn without_koronis(self) -> Self {
+        toggle_koronis(self)
+    }
+
+    pub fn has_quantity(self) -> bool {
+        use State::*;
+
+        matches!(
+            self,
+            Alpha(_, _, _, _, Quantity::Long | Quantity::Short)
+                | Iota(_, _, _, _, Quantity::Long | Quantity::Short)
+                | Ypsilon(_, _, _, _, Quantity::Long | Quantity::Short)
+        )
+    }
+
+    // Whether the glyph renders as a single code point carrying all of its marks.
+    pub fn is_precomposed(self) -> bool {
+        use State::*;
+
+        match self {
+            _ if self.has_quantity() => self.marks().count() == 1,
+            // The small tables fall back to the letter with a diaeresis alone.
+            Iota(
+                _,
+                Breathing::Smooth | Breathing::Rough | Breathing::Koronis,
+                _,
+                Diaeresis::Some,
+                _,
+            )
+            | Ypsilon(
+                _,
+                Breathing::Smooth | Breathing::Rough | Breathing::Koronis,
+                _,
+                Diaeresis::Some,
+                _,
+            ) => false,
+            _ => self.to_str().chars().count() == 1,
+        }
+    }
+
+    // Settles the glyph at the end of the buffer when no more input follows.
+    pub fn finish(self, options: &Options, buffer: &mut String) {
+        if let Self::Sigma(a) = self {
+            if !options.lunate_sigma {
+                let sigma = self.render(options);
+                let final_sigma = Self::FinalSigma(a).render(options);
+                replace(buffer, &sigma, &final_sigma);
+            }
+        }
+    }
+
+    // Puts an underdot on the glyph at the end of the buffer. It goes right after the letter,
+    // before any marks above, as in canonical order.
+    pub fn underdot(self, options: &Options, buffer: &mut String) {
+        let glyph = self.render(options);
+        let at = glyph
+            .char_indices()
+            .find(|(_, c)| c.is_alphabetic())
+            .map_or(glyph.len(), |(i, c)| i + c.len_utf8());
+
+        let mut dotted = glyph.to_string();
+        dotted.insert_str(at, uc::UNDERDOT);
+        replace(buffer, &glyph, &dotted);
+    }
+
+    // Whether `c` modifies this glyph rather than starting a new one.
+    pub fn continues(self, c: u8, scheme: &dyn Scheme) -> bool {
+        use State::*;
+
+        match self {
+            Initial | Asterisk(_, _, _, _) => false,
+            Symbol(_) | Keraia => scheme.symbol(c).is_some(),
+            Sigma(_) => scheme.sigma(c).is_some() || is_diacritics(c, scheme),
+            Apostrophe => is_vowel(c, scheme) || is_diacritics(c, scheme),
+            _ => is_diacritics(c, scheme),
+        }
+    }
+
+    pub fn rewrite_buffer(
+        self,
+        c: u8,
+        scheme: &dyn Scheme,
+        options: &Options,
+        buffer: &mut String,
+    ) -> Self {
         use State::*;
 
         let old = self;
-        let new = transit(old, c);
+        let new = transit(old, c, scheme, options);
 
         match old {
-            Initial => buffer.push_str(new.to_str()),
-            Sigma(a) => {
-                if matches!(new, Initial) {
-                    let sigma = old.to_str();
-                    let final_sigma = uc::FinalSigma[a];
-                    replace(buffer, sigma, final_sigma);
-                } else if !is_diacritics(c) {
-                    buffer.push_str(new.to_str());
-                }
+            Sigma(_) if new.ends_word() => {
+                old.finish(options, buffer);
+                buffer.push_str(&new.render(options));
             }
-            Alpha(_, _, _, _)
-            | Epsilon(_, _, _)
-            | Eta(_, _, _, _)
-            | Iota(_, _, _, _)
-            | Omicron(_, _, _)
-            | Ypsilon(_, _, _, _)
-            | Omega(_, _, _, _) => {
-                if is_diacritics(c) {
-                    let old = old.to_str();
-                    let new = new.to_str();
-                    replace(buffer, old, new);
-                } else {
-                    buffer.push_str(new.to_str());
-                }
+            _ if old.continues(c, scheme) => {
+                let old = old.render(options);
+                let new = new.render(options);
+                replace(buffer, &old, &new);
             }
-            _ => {
-                if !is_diacritics(c) {
-                    buffer.push_str(new.to_str());
-                }
+            _ if old.passes_koronis(new) => {
+                let bare = old.without_koronis().render(options);
+                replace(buffer, &old.render(options), &bare);
+                buffer.push_str(&new.render(options));
             }
+            _ => buffer.push_str(&new.render(options)),
         }
 
         new
@@ -0,0 +1,298 @@
+use crate::state::{self, letter, State};
+use crate::{Accent, Case, Diaeresis, Letter, Quantity, Subscript};
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+use Letter::*;
+
+// Consonant clusters that can begin a word, besides single consonants. Within a word they go
+// with the vowel after them.
+const INITIAL_CLUSTERS: &[&[Letter]] = &[
+    &[Beta, Delta],
+    &[Beta, Lambda],
+    &[Beta, Rho],
+    &[Gamma, Lambda],
+    &[Gamma, Nu],
+    &[Gamma, Rho],
+    &[Delta, Mu],
+    &[Delta, Nu],
+    &[Delta, Rho],
+    &[Theta, Lambda],
+    &[Theta, Nu],
+    &[Theta, Rho],
+    &[Kappa, Lambda],
+    &[Kappa, Mu],
+    &[Kappa, Nu],
+    &[Kappa, Rho],
+    &[Kappa, Tau],
+    &[Mu, Nu],
+    &[Pi, Lambda],
+    &[Pi, Nu],
+    &[Pi, Rho],
+    &[Pi, Tau],
+    &[Sigma, Beta],
+    &[Sigma, Theta],
+    &[Sigma, Kappa],
+    &[Sigma, Mu],
+    &[Sigma, Pi],
+    &[Sigma, Tau],
+    &[Sigma, Phi],
+    &[Sigma, Chi],
+    &[Tau, Lambda],
+    &[Tau, Mu],
+    &[Tau, Rho],
+    &[Phi, Theta],
+    &[Phi, Lambda],
+    &[Phi, Nu],
+    &[Phi, Rho],
+    &[Chi, Theta],
+    &[Chi, Lambda],
+    &[Chi, Mu],
+    &[Chi, Nu],
+    &[Chi, Rho],
+    &[Sigma, Kappa, Lambda],
+    &[Sigma, Kappa, Rho],
+    &[Sigma, Pi, Lambda],
+    &[Sigma, Pi, Rho],
+    &[Sigma, Tau, Rho],
+    &[Sigma, Phi, Rho],
+];
+
+// One syllable of a text, by character range.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct Syllable {
+    pub start: usize,
+    pub end: usize,
+    // Index of the word in the text.
+    pub word: usize,
+    // Whether it ends in a consonant, counting ζ, ξ and ψ as two consonants.
+    pub closed: bool,
+}
+
+// A run of letters, with the character range of each letter in the text.
+pub struct Word {
+    pub letters: Vec<State>,
+    pub ranges: Vec<Range<usize>>,
+}
+
+pub fn is(state: State, l: Letter) -> bool {
+    state.bare() == letter(l, Case::Small)
+}
+
+fn is_letter(state: State) -> bool {
+    state.is_vowel() || state.is_consonant()
+}
+
+// ζ, ξ and ψ each stand for two consonants.
+pub fn is_double(state: State) -> bool {
+    is(state, Zeta) || is(state, Xi) || is(state, Psi)
+}
+
+// A diaeresis keeps the vowel apart from the one before it.
+pub fn has_diaeresis(state: State) -> bool {
+    matches!(
+        state,
+        State::Iota(_, _, _, Diaeresis::Some, _) | State::Ypsilon(_, _, _, Diaeresis::Some, _)
+    )
+}
+
+pub fn is_diphthong(first: State, second: State) -> bool {
+    if has_diaeresis(second) {
+        return false;
+    }
+
+    if is(second, Iota) {
+        [Alpha, Epsilon, Omicron, Ypsilon]
+            .into_iter()
+            .any(|l| is(first, l))
+    } else if is(second, Ypsilon) {
+        [Alpha, Epsilon, Eta, Omicron]
+            .into_iter()
+            .any(|l| is(first, l))
+    } else {
+        false
+    }
+}
+
+fn can_begin_word(cluster: &[State]) -> bool {
+    cluster.len() == 1
+        || INITIAL_CLUSTERS.iter().any(|initial| {
+            initial.len() == cluster.len() && cluster.iter().zip(*initial).all(|(&s, &l)| is(s, l))
+        })
+}
+
+// Ranges of the vowels or diphthongs of a word, by letter index.
+pub fn nuclei(letters: &[State]) -> Vec<Range<usize>> {
+    let mut nuclei: Vec<Range<usize>> = Vec::new();
+
+    for (i, &state) in letters.iter().enumerate() {
+        if !state.is_vowel() {
+            continue;
+        }
+        match nuclei.last_mut() {
+            Some(nucleus)
+                if nucleus.end == i
+                    && nucleus.len() == 1
+                    && is_diphthong(letters[i - 1], state) =>
+            {
+                nucleus.end = i + 1;
+            }
+            _ => nuclei.push(i..i + 1),
+        }
+    }
+
+    nuclei
+}
+
+// Splits a word into syllables, as ranges of its letters. A word without vowels, such as an
+// elided δ’, has none.
+pub fn split(letters: &[State]) -> Vec<Range<usize>> {
+    let nuclei = nuclei(letters);
+    let mut syllables: Vec<Range<usize>> = Vec::new();
+
+    for (i, nucleus) in nuclei.iter().enumerate() {
+        let start = match i {
+            0 => 0,
+            _ => {
+                // The longest tail of the consonants in between that can begin a word goes with
+                // this vowel, so doubled consonants are split.
+                let cluster = nuclei[i - 1].end..nucleus.start;
+                cluster
+                    .clone()
+                    .find(|&j| can_begin_word(&letters[j..cluster.end]))
+                    .unwrap_or(cluster.end)
+            }
+        };
+        if let Some(last) = syllables.last_mut() {
+            last.end = start;
+        }
+        syllables.push(start..letters.len());
+    }
+
+    syllables
+}
+
+// The length of a vowel or diphthong by nature, or `Quantity::None` for an α, ι or υ that
+// could be either.
+pub fn length(letters: &[State], nucleus: &Range<usize>) -> Quantity {
+    if nucleus.len() > 1 {
+        return Quantity::Long;
+    }
+
+    match letters[nucleus.start] {
+        State::Eta(_, _, _, _) | State::Omega(_, _, _, _) => Quantity::Long,
+        State::Epsilon(_, _, _) | State::Omicron(_, _, _) => Quantity::Short,
+        // A marked quantity wins, so that a circumflex on a short vowel can be pointed out.
+        State::Alpha(_, _, _, _, q @ (Quantity::Long | Quantity::Short))
+        | State::Iota(_, _, _, _, q @ (Quantity::Long | Quantity::Short))
+        | State::Ypsilon(_, _, _, _, q @ (Quantity::Long | Quantity::Short)) => q,
+        State::Alpha(_, _, Accent::Circumflex, _, _)
+        | State::Alpha(_, _, _, Subscript::Iota, _)
+        | State::Iota(_, _, Accent::Circumflex, _, _)
+        | State::Ypsilon(_, _, Accent::Circumflex, _, _) => Quantity::Long,
+        _ => Quantity::None,
+    }
+}
+
+// Whether the syllable ends in a consonant, counting ζ, ξ and ψ as two.
+pub fn is_closed(letters: &[State], syllable: &Range<usize>) -> bool {
+    letters[syllable.end - 1].is_consonant()
+        || letters
+            .get(syllable.end)
+            .is_some_and(|&next| is_double(next))
+}
+
+// The runs of letters of Greek text.
+pub fn words(s: &str) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut in_word = false;
+
+    for (state, range) in state::read(s) {
+        if !is_letter(state) {
+            in_word = false;
+            continue;
+        }
+        if !in_word {
+            words.push(Word {
+                letters: Vec::new(),
+                ranges: Vec::new(),
+            });
+            in_word = true;
+        }
+        if let Some(word) = words.last_mut() {
+            word.letters.push(state);
+            word.ranges.push(range);
+        }
+    }
+
+    words
+}
+
+pub fn syllabify(s: &str) -> Vec<Syllable> {
+    let mut syllables = Vec::new();
+
+    for (i, word) in words(s).iter().enumerate() {
+        for syllable in split(&word.letters) {
+            syllables.push(Syllable {
+                start: word.ranges[syllable.start].start,
+                end: word.ranges[syllable.end - 1].end,
+                word: i,
+                closed: is_closed(&word.letters, &syllable),
+            });
+        }
+    }
+
+    syllables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The words of `s`, with a hyphen between syllables and a space between words.
+    fn hyphenate(s: &str) -> String {
+        let chars: Vec<char> = s.chars().collect();
+        let syllables = syllabify(s);
+        let mut hyphenated = String::new();
+        for (i, syllable) in syllables.iter().enumerate() {
+            if i > 0 {
+                hyphenated.push(match syllables[i - 1].word == syllable.word {
+                    true => '-',
+                    false => ' ',
+                });
+            }
+            hyphenated.extend(&chars[syllable.start..syllable.end]);
+        }
+        hyphenated
+    }
+
+    #[test]
+    fn splits_between_consonants() {
+        assert_eq!(hyphenate("ἄνθρωπος"), "ἄν-θρω-πος");
+        assert_eq!(hyphenate("θάλασσα"), "θά-λασ-σα");
+        assert_eq!(hyphenate("πατρός"), "πα-τρός");
+    }
+
+    #[test]
+    fn keeps_diphthongs_together() {
+        assert_eq!(hyphenate("παιδεύουσι"), "παι-δεύ-ου-σι");
+        assert_eq!(hyphenate("πραΰς"), "πρα-ΰς");
+    }
+
+    #[test]
+    fn marks_closed_syllables() {
+        let closed: Vec<bool> = syllabify("ἄνθρωπος").iter().map(|s| s.closed).collect();
+        assert_eq!(closed, [true, false, true]);
+        // ξ stands for two consonants, so the syllable before it is closed.
+        let closed: Vec<bool> = syllabify("ἔξω").iter().map(|s| s.closed).collect();
+        assert_eq!(closed, [true, false]);
+    }
+
+    #[test]
+    fn skips_elided_words() {
+        assert_eq!(hyphenate("δ’ ἄνδρα"), "ἄν-δρα");
+    }
+}
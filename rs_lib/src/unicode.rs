@@ -1,5 +1,12 @@
+// Each cell holds at most one code point. The capital tables leave a cell empty if Unicode has no
+// code point for the combination; the small tables fall back to a code point with fewer marks
+// instead: ε and ο with a circumflex to the letter without it, and ι and υ with a breathing and
+// a diaeresis to the letter with the diaeresis alone. `State::is_precomposed` tells these apart.
 // [[no accent, grave, acute, circumflex](no breathing, smooth, rough)](no subscript, iota subscript)
 // [[no accent, grave, acute, circumflex](no breathing, smooth, rough)](no diaeresis, diaeresis)
+// [no quantity, macron, breve]
+use crate::scheme::Diacritic;
+
 mod vowel;
 pub use vowel::*;
 
@@ -7,3 +14,55 @@ mod consonant;
 pub use consonant::*;
 
 pub const KORONIS: &str = "\u{1fbd}";
+pub const SYMBOL: &str = "#";
+pub const UNDERDOT: &str = "\u{0323}";
+
+pub const ANO_TELEIA: &str = "\u{0387}";
+pub const MIDDLE_DOT: &str = "\u{00b7}";
+pub const QUESTION_MARK: &str = "\u{037e}";
+pub const SEMICOLON: &str = ";";
+pub const APOSTROPHE: &str = "\u{2019}";
+pub const MODIFIER_APOSTROPHE: &str = "\u{02bc}";
+
+pub const KERAIA: &str = "\u{0374}";
+pub const PRIME: &str = "\u{02b9}";
+pub const LOWER_KERAIA: &str = "\u{0375}";
+
+pub fn combining(diacritic: Diacritic) -> char {
+    match diacritic {
+        // U+0343 (combining koronis) normalizes to the smooth breathing.
+        Diacritic::Smooth | Diacritic::Koronis => '\u{0313}',
+        Diacritic::Rough => '\u{0314}',
+        Diacritic::Grave => '\u{0300}',
+        Diacritic::Acute => '\u{0301}',
+        Diacritic::Circumflex => '\u{0342}',
+        Diacritic::Iota => '\u{0345}',
+        Diacritic::Diaeresis => '\u{0308}',
+        Diacritic::Macron => '\u{0304}',
+        Diacritic::Breve => '\u{0306}',
+    }
+}
+
+// Greek Extended oxia equivalent of an acute-accented Greek and Coptic (tonos) code point.
+pub fn oxia(c: char) -> char {
+    match c {
+        '\u{03ac}' => '\u{1f71}',
+        '\u{03ad}' => '\u{1f73}',
+        '\u{03ae}' => '\u{1f75}',
+        '\u{03af}' => '\u{1f77}',
+        '\u{03cc}' => '\u{1f79}',
+        '\u{03cd}' => '\u{1f7b}',
+        '\u{03ce}' => '\u{1f7d}',
+        '\u{0390}' => '\u{1fd3}',
+        '\u{03b0}' => '\u{1fe3}',
+        '\u{0386}' => '\u{1fbb}',
+        '\u{0388}' => '\u{1fc9}',
+        '\u{0389}' => '\u{1fcb}',
+        '\u{038a}' => '\u{1fdb}',
+        '\u{038c}' => '\u{1ff9}',
+        '\u{038e}' => '\u{1feb}',
+        '\u{038f}' => '\u{1ffb}',
+        '\u{0384}' => '\u{1ffd}',
+        _ => c,
+    }
+}
@@ -10,7 +10,8 @@ impl Index<(Case, Breathing)> for Rho {
     fn index(&self, v: (Case, Breathing)) -> &Self::Output {
         match v {
             (Case::Capital, Breathing::Rough) => &"\u{1fec}",
-            (Case::Capital, Breathing::None) => &"\u{03a1}",
+            (Case::Capital, Breathing::Smooth) => &"",
+            (Case::Capital, _) => &"\u{03a1}",
             (Case::Small, Breathing::Rough) => &"\u{1fe5}",
             (Case::Small, Breathing::Smooth) => &"\u{1fe4}",
             _ => &"\u{03c1}",
@@ -49,6 +50,13 @@ impl_consonant_index! { Pi, "\u{03c0}", "\u{03a0}" }
 impl_consonant_index! { Tau, "\u{03c4}", "\u{03a4}" }
 impl_consonant_index! { Sigma, "\u{03c3}", "\u{03a3}" }
 impl_consonant_index! { FinalSigma, "\u{03c2}", "\u{03a3}" }
+impl_consonant_index! { LunateSigma, "\u{03f2}", "\u{03f9}" }
 impl_consonant_index! { Phi, "\u{03c6}", "\u{03a6}" }
 impl_consonant_index! { Chi, "\u{03c7}", "\u{03a7}" }
 impl_consonant_index! { Psi, "\u{03c8}", "\u{03a8}" }
+impl_consonant_index! { Digamma, "\u{03dd}", "\u{03dc}" }
+impl_consonant_index! { Heta, "\u{0371}", "\u{0370}" }
+impl_consonant_index! { Koppa, "\u{03d9}", "\u{03d8}" }
+impl_consonant_index! { NumeralKoppa, "\u{03df}", "\u{03de}" }
+impl_consonant_index! { Stigma, "\u{03db}", "\u{03da}" }
+impl_consonant_index! { Sampi, "\u{03e1}", "\u{03e0}" }
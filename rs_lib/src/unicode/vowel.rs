@@ -1,10 +1,10 @@
-use crate::{Accent, Breathing, Case, Diaeresis, Subscript};
+use crate::{Accent, Breathing, Case, Diaeresis, Quantity, Subscript};
 
 use std::ops::Index;
 
 fn breathing_idx(v: Breathing) -> usize {
     match v {
-        Breathing::Smooth => 4,
+        Breathing::Smooth | Breathing::Koronis => 4,
         Breathing::Rough => 8,
         _ => 0,
     }
@@ -31,6 +31,13 @@ fn diaeresis_idx(v: Diaeresis) -> usize {
         0
     }
 }
+fn quantity_idx(v: Quantity) -> usize {
+    match v {
+        Quantity::Long => 1,
+        Quantity::Short => 2,
+        _ => 0,
+    }
+}
 
 macro_rules! impl_vowel_index {
     ($ty: ident, (Breathing, Accent, Subscript), $small: expr, $capital: expr) => {
@@ -91,6 +98,80 @@ impl_vowel_index! { Omicron, (Breathing, Accent), SMALL_OMICRON, CAP_OMICRON }
 impl_vowel_index! { Ypsilon, (Breathing, Accent, Diaeresis), SMALL_YPSILON, CAP_YPSILON }
 impl_vowel_index! { Omega, (Breathing, Accent, Subscript), SMALL_OMEGA, CAP_OMEGA }
 
+// The bare letter with a macron or breve.
+macro_rules! impl_quantity_index {
+    ($ty: ident, $small: expr, $capital: expr) => {
+        impl Index<(Case, Quantity)> for $ty {
+            type Output = &'static str;
+
+            fn index(&self, v: (Case, Quantity)) -> &Self::Output {
+                let idx = quantity_idx(v.1);
+
+                match v.0 {
+                    Case::Small => &$small[idx],
+                    Case::Capital => &$capital[idx],
+                }
+            }
+        }
+    };
+}
+
+impl_quantity_index! { Alpha, SMALL_ALPHA_QUANTITY, CAP_ALPHA_QUANTITY }
+impl_quantity_index! { Iota, SMALL_IOTA_QUANTITY, CAP_IOTA_QUANTITY }
+impl_quantity_index! { Ypsilon, SMALL_YPSILON_QUANTITY, CAP_YPSILON_QUANTITY }
+
+const SMALL_ALPHA_QUANTITY: &[&str] = &["\u{03b1}", "\u{1fb1}", "\u{1fb0}"];
+const SMALL_IOTA_QUANTITY: &[&str] = &["\u{03b9}", "\u{1fd1}", "\u{1fd0}"];
+const SMALL_YPSILON_QUANTITY: &[&str] = &["\u{03c5}", "\u{1fe1}", "\u{1fe0}"];
+const CAP_ALPHA_QUANTITY: &[&str] = &["\u{0391}", "\u{1fb9}", "\u{1fb8}"];
+const CAP_IOTA_QUANTITY: &[&str] = &["\u{0399}", "\u{1fd9}", "\u{1fd8}"];
+const CAP_YPSILON_QUANTITY: &[&str] = &["\u{03a5}", "\u{1fe9}", "\u{1fe8}"];
+
+// A missing code point must be left empty; spelling it with spacing marks loses the letter
+// they are meant for.
+const fn is_code_point(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.is_empty() {
+        return true;
+    }
+
+    let width = match bytes[0] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    };
+    bytes.len() == width
+}
+
+const fn is_table(table: &[&str]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if !is_code_point(table[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const _: () = assert!(
+    is_table(SMALL_ALPHA)
+        && is_table(SMALL_EPSILON)
+        && is_table(SMALL_ETA)
+        && is_table(SMALL_IOTA)
+        && is_table(SMALL_OMICRON)
+        && is_table(SMALL_YPSILON)
+        && is_table(SMALL_OMEGA)
+        && is_table(CAP_ALPHA)
+        && is_table(CAP_EPSILON)
+        && is_table(CAP_ETA)
+        && is_table(CAP_IOTA)
+        && is_table(CAP_OMICRON)
+        && is_table(CAP_YPSILON)
+        && is_table(CAP_OMEGA)
+);
+
 const SMALL_ALPHA: &[&str] = &[
     "\u{03b1}", "\u{1f70}", "\u{03ac}", "\u{1fb6}", "\u{1f00}", "\u{1f02}", "\u{1f04}", "\u{1f06}",
     "\u{1f01}", "\u{1f03}", "\u{1f05}", "\u{1f07}", "\u{1fb3}", "\u{1fb2}", "\u{1fb4}", "\u{1fb7}",
@@ -126,140 +207,140 @@ const SMALL_OMEGA: &[&str] = &[
 ];
 
 const CAP_ALPHA: &[&str] = &[
-    "\u{0391}",
-    "\u{1fba}",
-    "\u{0386}",
-    "\u{1fc0}\u{0391}",
-    "\u{1f08}",
-    "\u{1f0a}",
-    "\u{1f0c}",
-    "\u{1f0e}",
-    "\u{1f09}",
-    "\u{1f0b}",
-    "\u{1f0d}",
-    "\u{1f0f}",
-    "\u{1fbc}",
-    "\u{1fef}\u{1fbc}",
-    "\u{0384}\u{1fbc}",
-    "\u{1fc0}\u{1fbc}",
-    "\u{1f88}",
-    "\u{1f8a}",
-    "\u{1f8c}",
-    "\u{1f8e}",
-    "\u{1f89}",
-    "\u{1f8b}",
-    "\u{1f8d}",
-    "\u{1f8f}",
+    "\u{0391}", "\u{1fba}", "\u{0386}", "", "\u{1f08}", "\u{1f0a}", "\u{1f0c}", "\u{1f0e}",
+    "\u{1f09}", "\u{1f0b}", "\u{1f0d}", "\u{1f0f}", "\u{1fbc}", "", "", "", "\u{1f88}", "\u{1f8a}",
+    "\u{1f8c}", "\u{1f8e}", "\u{1f89}", "\u{1f8b}", "\u{1f8d}", "\u{1f8f}",
 ];
 const CAP_EPSILON: &[&str] = &[
     "\u{0395}", "\u{1fc8}", "\u{0388}", "\u{0395}", "\u{1f18}", "\u{1f1a}", "\u{1f1c}", "\u{1f18}",
     "\u{1f19}", "\u{1f1b}", "\u{1f1d}", "\u{1f19}",
 ];
 const CAP_ETA: &[&str] = &[
-    "\u{0397}",
-    "\u{1fca}",
-    "\u{0389}",
-    "\u{1fc0}\u{0397}",
-    "\u{1f28}",
-    "\u{1f2a}",
-    "\u{1f2c}",
-    "\u{1f2e}",
-    "\u{1f29}",
-    "\u{1f2b}",
-    "\u{1f2d}",
-    "\u{1f2f}",
-    "\u{1fcc}",
-    "\u{1fef}\u{1fcc}",
-    "\u{0384}\u{1fcc}",
-    "\u{1fc0}\u{1fcc}",
-    "\u{1f98}",
-    "\u{1f9a}",
-    "\u{1f9c}",
-    "\u{1f9e}",
-    "\u{1f99}",
-    "\u{1f9b}",
-    "\u{1f9d}",
-    "\u{1f9f}",
+    "\u{0397}", "\u{1fca}", "\u{0389}", "", "\u{1f28}", "\u{1f2a}", "\u{1f2c}", "\u{1f2e}",
+    "\u{1f29}", "\u{1f2b}", "\u{1f2d}", "\u{1f2f}", "\u{1fcc}", "", "", "", "\u{1f98}", "\u{1f9a}",
+    "\u{1f9c}", "\u{1f9e}", "\u{1f99}", "\u{1f9b}", "\u{1f9d}", "\u{1f9f}",
 ];
 const CAP_IOTA: &[&str] = &[
-    "\u{0399}",
-    "\u{1fda}",
-    "\u{038a}",
-    "\u{1fc0}\u{0399}",
-    "\u{1f38}",
-    "\u{1f3a}",
-    "\u{1f3c}",
-    "\u{1f3e}",
-    "\u{1f39}",
-    "\u{1f3b}",
-    "\u{1f3d}",
-    "\u{1f3f}",
-    "\u{03aa}",
-    "\u{1fef}\u{03aa}",
-    "\u{0384}\u{03aa}",
-    "\u{1fc0}\u{03aa}",
-    "\u{1fbf}\u{03aa}",
-    "\u{1fcd}\u{03aa}",
-    "\u{1fce}\u{03aa}",
-    "\u{1fcf}\u{03aa}",
-    "\u{1ffe}\u{03aa}",
-    "\u{1fdd}\u{03aa}",
-    "\u{1fde}\u{03aa}",
-    "\u{1fdf}\u{03aa}",
+    "\u{0399}", "\u{1fda}", "\u{038a}", "", "\u{1f38}", "\u{1f3a}", "\u{1f3c}", "\u{1f3e}",
+    "\u{1f39}", "\u{1f3b}", "\u{1f3d}", "\u{1f3f}", "\u{03aa}", "", "", "", "", "", "", "", "", "",
+    "", "",
 ];
 const CAP_OMICRON: &[&str] = &[
     "\u{039f}", "\u{1ff8}", "\u{038c}", "\u{039f}", "\u{1f48}", "\u{1f4a}", "\u{1f4c}", "\u{1f48}",
     "\u{1f49}", "\u{1f4b}", "\u{1f4d}", "\u{1f49}",
 ];
 const CAP_YPSILON: &[&str] = &[
-    "\u{03a5}",
-    "\u{1fea}",
-    "\u{038e}",
-    "\u{1fc0}\u{03a5}",
-    "\u{1fbf}\u{03a5}",
-    "\u{1fcd}\u{03a5}",
-    "\u{1fce}\u{03a5}",
-    "\u{1fcf}\u{03a5}",
-    "\u{1f59}",
-    "\u{1f5b}",
-    "\u{1f5d}",
-    "\u{1f5f}",
-    "\u{03ab}",
-    "\u{1fef}\u{03ab}",
-    "\u{0384}\u{03ab}",
-    "\u{1fc0}\u{03ab}",
-    "\u{1fbf}\u{03ab}",
-    "\u{1fcd}\u{1fef}",
-    "\u{1fce}\u{03ab}",
-    "\u{1fcf}\u{03ab}",
-    "\u{1ffe}\u{03ab}",
-    "\u{1fdd}\u{03ab}",
-    "\u{1fde}\u{03ab}",
-    "\u{1fdf}\u{03ab}",
+    "\u{03a5}", "\u{1fea}", "\u{038e}", "", "", "", "", "", "\u{1f59}", "\u{1f5b}", "\u{1f5d}",
+    "\u{1f5f}", "\u{03ab}", "", "", "", "", "", "", "", "", "", "", "",
 ];
 const CAP_OMEGA: &[&str] = &[
-    "\u{03a9}",
-    "\u{1ffa}",
-    "\u{038f}",
-    "\u{1fc0}\u{03a9}",
-    "\u{1f68}",
-    "\u{1f6a}",
-    "\u{1f6c}",
-    "\u{1f6e}",
-    "\u{1f69}",
-    "\u{1f6b}",
-    "\u{1f6d}",
-    "\u{1f6f}",
-    "\u{1ffc}",
-    "\u{1fef}\u{1ffc}",
-    "\u{0384}\u{1ffc}",
-    "\u{1fc0}\u{1ffc}",
-    "\u{1fa8}",
-    "\u{1faa}",
-    "\u{1fac}",
-    "\u{1fae}",
-    "\u{1fa9}",
-    "\u{1fab}",
-    "\u{1fad}",
-    "\u{1faf}",
+    "\u{03a9}", "\u{1ffa}", "\u{038f}", "", "\u{1f68}", "\u{1f6a}", "\u{1f6c}", "\u{1f6e}",
+    "\u{1f69}", "\u{1f6b}", "\u{1f6d}", "\u{1f6f}", "\u{1ffc}", "", "", "", "\u{1fa8}", "\u{1faa}",
+    "\u{1fac}", "\u{1fae}", "\u{1fa9}", "\u{1fab}", "\u{1fad}", "\u{1faf}",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::Diacritic;
+    use crate::unicode::combining;
+
+    use unicode_normalization::UnicodeNormalization;
+
+    type SubscriptTable = dyn Index<(Case, Breathing, Accent, Subscript), Output = &'static str>;
+    type DiaeresisTable = dyn Index<(Case, Breathing, Accent, Diaeresis), Output = &'static str>;
+
+    const BREATHINGS: [Breathing; 3] = [Breathing::None, Breathing::Smooth, Breathing::Rough];
+    const ACCENTS: [Accent; 4] = [
+        Accent::None,
+        Accent::Grave,
+        Accent::Acute,
+        Accent::Circumflex,
+    ];
+
+    fn marks(breathing: Breathing, accent: Accent, extra: Option<Diacritic>) -> Vec<char> {
+        let breathing = match breathing {
+            Breathing::Smooth => Some(Diacritic::Smooth),
+            Breathing::Rough => Some(Diacritic::Rough),
+            _ => None,
+        };
+        let accent = match accent {
+            Accent::Grave => Some(Diacritic::Grave),
+            Accent::Acute => Some(Diacritic::Acute),
+            Accent::Circumflex => Some(Diacritic::Circumflex),
+            Accent::None => None,
+        };
+
+        // Canonical order: breathing, diaeresis, accent, iota subscript.
+        let (diaeresis, iota) = match extra {
+            Some(Diacritic::Diaeresis) => (extra, None),
+            _ => (None, extra),
+        };
+        [breathing, diaeresis, accent, iota]
+            .into_iter()
+            .flatten()
+            .map(combining)
+            .collect()
+    }
+
+    // A cell holds the precomposed code point of its letter and marks, or is empty exactly when
+    // Unicode has none.
+    fn check(cell: &str, base: char, marks: Vec<char>) {
+        let spelled: String = std::iter::once(base).chain(marks.iter().copied()).collect();
+        let composed: String = spelled.nfc().collect();
+
+        if composed.chars().count() > 1 {
+            assert_eq!(cell, "", "{spelled:?} has no code point");
+            return;
+        }
+        assert_eq!(cell, composed, "{spelled:?}");
+
+        let mut decomposed = cell.nfd();
+        assert_eq!(decomposed.next(), Some(base), "{cell:?}");
+        let mut rest: Vec<char> = decomposed.collect();
+        let mut marks = marks;
+        rest.sort();
+        marks.sort();
+        assert_eq!(rest, marks, "{cell:?}");
+    }
+
+    #[test]
+    fn capitals_with_subscript() {
+        let tables: [(&SubscriptTable, char); 3] = [
+            (&Alpha, '\u{0391}'),
+            (&Eta, '\u{0397}'),
+            (&Omega, '\u{03a9}'),
+        ];
+
+        for (table, base) in tables {
+            for b in BREATHINGS {
+                for a in ACCENTS {
+                    for (s, extra) in [
+                        (Subscript::None, None),
+                        (Subscript::Iota, Some(Diacritic::Iota)),
+                    ] {
+                        check(table[(Case::Capital, b, a, s)], base, marks(b, a, extra));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn capitals_with_diaeresis() {
+        let tables: [(&DiaeresisTable, char); 2] = [(&Iota, '\u{0399}'), (&Ypsilon, '\u{03a5}')];
+
+        for (table, base) in tables {
+            for b in BREATHINGS {
+                for a in ACCENTS {
+                    for (d, extra) in [
+                        (Diaeresis::None, None),
+                        (Diaeresis::Some, Some(Diacritic::Diaeresis)),
+                    ] {
+                        check(table[(Case::Capital, b, a, d)], base, marks(b, a, extra));
+                    }
+                }
+            }
+        }
+    }
+}
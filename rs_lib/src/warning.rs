@@ -0,0 +1,65 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    // A circumflex typed on epsilon or omicron.
+    CircumflexOnShortVowel,
+    // A diacritic typed after a letter that cannot carry it.
+    DiacriticOnConsonant,
+    // A diacritic the vowel cannot carry, e.g. an iota subscript on epsilon.
+    UnsupportedDiacritic,
+    // A diacritic key with no letter before it; it is copied as-is. Also a Beta Code asterisk,
+    // with any marks after it, that no letter follows; it is dropped.
+    DiacriticWithoutLetter,
+    // The combination has no precomposed code point and is rendered with combining marks.
+    NoPrecomposedForm,
+    // An accent further back than the law of limitation allows: before the antepenult, on the
+    // antepenult before a long ultima, or a circumflex on the penult before a long ultima.
+    LawOfLimitation,
+    // A circumflex on a syllable known to be short, e.g. an α marked with a breve.
+    CircumflexOnShortSyllable,
+    // An acute on a long penult before a short ultima, which takes a circumflex as in σωτῆρα.
+    SoteraRule,
+    // A breathing typed on a vowel that took the koronis of the apostrophe before it, as in
+    // `d'a)n`; the apostrophe is read as crasis and not kept. A space keeps it as elision.
+    BreathingAfterCrasis,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Warning {
+    kind: WarningKind,
+    position: usize,
+    sequence: String,
+}
+
+#[wasm_bindgen]
+impl Warning {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    // Input char offset of the offending character, as for `Span`.
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Input of the glyph the warning is about, up to the offending character.
+    #[wasm_bindgen(getter)]
+    pub fn sequence(&self) -> String {
+        self.sequence.clone()
+    }
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, position: usize, sequence: String) -> Self {
+        Self {
+            kind,
+            position,
+            sequence,
+        }
+    }
+}